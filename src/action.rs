@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use strum::Display;
 
-use crate::components::home::Tool;
+use crate::{
+    components::home::Tool,
+    drawing::{export::Wrap, Align, Axis},
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Display, Serialize, Deserialize)]
 pub enum Action {
@@ -20,17 +23,66 @@ pub enum Action {
     CloseCommandPalette,
 
     SwitchTool(Tool),
+    SetBrush(char),
+    SetTitle(String),
     #[serde(skip)]
     EditText,
     CommitText,
+    FinishPolyline,
     SelectAll,
     SelectNone,
     Delete,
+    Yank,
+    Paste,
+    Duplicate,
+    Raise,
+    Lower,
+    RaiseToTop,
+    LowerToBottom,
+    Group,
+    Ungroup,
+    Align(Align),
+    Distribute(Axis),
+
+    FocusLayers,
+    FocusCanvas,
+    LayerCursorUp,
+    LayerCursorDown,
+    LayerSelectUp,
+    LayerSelectDown,
+    ToggleLayer,
+    ToggleGroup,
+    FocusStyle,
+    StyleCursorUp,
+    StyleCursorDown,
+    PropertyIncrease,
+    PropertyDecrease,
+    ActivateProperty,
+    #[serde(skip)]
+    EditProperty,
+    CommitProperty,
+    CancelProperty,
+    #[serde(skip)]
+    CopyToClipboard(String),
+    Undo,
+    Redo,
+    ToggleRulers,
+    SetGrid(u16),
 
     ScrollUp,
     ScrollDown,
     ScrollLeft,
     ScrollRight,
 
+    New,
+    Open(String),
+    Save(String),
     Export(String),
+    ExportAscii(String),
+    ExportSelected(String),
+    ExportSvg(String),
+    ExportHtml(String),
+    ExportAnsi(String),
+    ExportWrapped(Wrap, String),
+    Import(String),
 }
@@ -3,7 +3,7 @@ use crossterm::event::KeyEvent;
 use ratatui::prelude::Rect;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
-use tracing::{debug, info};
+use tracing::{debug, error, info};
 
 use crate::{
     action::Action,
@@ -31,6 +31,9 @@ pub enum Mode {
     Home,
     EditingText,
     CommandPalette,
+    Layers,
+    Style,
+    EditingProperty,
 }
 
 impl App {
@@ -152,10 +155,19 @@ impl App {
                 Action::ClearScreen => tui.terminal.clear()?,
                 Action::Resize(w, h) => self.handle_resize(tui, w, h)?,
                 Action::Render => self.render(tui)?,
+                Action::Error(ref msg) => error!("{msg}"),
+                Action::CopyToClipboard(ref text) if self.config.config.osc52_clipboard => {
+                    tui.copy_to_clipboard(text)?
+                }
                 Action::EditText => self.mode = Mode::EditingText,
                 Action::CommitText => self.mode = Mode::Home,
                 Action::OpenCommandPalette => self.mode = Mode::CommandPalette,
                 Action::CloseCommandPalette => self.mode = Mode::Home,
+                Action::FocusLayers => self.mode = Mode::Layers,
+                Action::FocusCanvas => self.mode = Mode::Home,
+                Action::FocusStyle => self.mode = Mode::Style,
+                Action::EditProperty => self.mode = Mode::EditingProperty,
+                Action::CommitProperty | Action::CancelProperty => self.mode = Mode::Style,
                 _ => {}
             }
             for component in self.components.iter_mut() {
@@ -199,4 +211,6 @@ pub mod color_scheme {
     pub const FG_SECONDARY: Color = Color::Rgb(161, 161, 170);
     pub const FG_MUTED: Color = Color::Rgb(113, 113, 122);
     pub const FG_SELECTION: Color = Color::Rgb(227, 152, 0);
+    pub const FG_GUIDE: Color = Color::Rgb(34, 211, 238);
+    pub const FG_ERROR: Color = Color::Rgb(248, 113, 113);
 }
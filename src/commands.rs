@@ -3,7 +3,12 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use crate::action::{Action, Action::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    action::{Action, Action::*},
+    drawing,
+};
 
 type Completer = fn(&str) -> Vec<String>;
 fn completer_path(input: &str) -> Vec<String> {
@@ -64,11 +69,57 @@ fn completer_path(input: &str) -> Vec<String> {
     v
 }
 
+fn completer_brush(input: &str) -> Vec<String> {
+    ["█", "▓", "▒", "░", "#", "*", "+", "·"]
+        .iter()
+        .filter(|brush| brush.starts_with(input))
+        .map(|brush| brush.to_string())
+        .collect()
+}
+
+fn completer_align(input: &str) -> Vec<String> {
+    drawing::Align::iter()
+        .map(|align| align.to_string())
+        .filter(|align| align.starts_with(input))
+        .collect()
+}
+
+fn completer_axis(input: &str) -> Vec<String> {
+    drawing::Axis::iter()
+        .map(|axis| axis.to_string())
+        .filter(|axis| axis.starts_with(input))
+        .collect()
+}
+
+fn completer_wrap(input: &str) -> Vec<String> {
+    drawing::export::Wrap::iter()
+        .filter(|wrap| *wrap != drawing::export::Wrap::None)
+        .map(|wrap| wrap.to_string())
+        .filter(|wrap| wrap.starts_with(input))
+        .collect()
+}
+
+fn completer_grid(input: &str) -> Vec<String> {
+    ["0", "2", "4", "8"]
+        .iter()
+        .filter(|size| size.starts_with(input))
+        .map(|size| size.to_string())
+        .collect()
+}
+
+/// Free text, which there's nothing to complete.
+fn completer_text(_input: &str) -> Vec<String> {
+    vec![]
+}
+
 struct Command {
     name: &'static str,
     aliases: &'static [&'static str],
     description: &'static str,
     args: &'static [Completer],
+    /// Whether the last argument takes the rest of the line, spaces and all,
+    /// like a file name or a title. Otherwise, every argument is one word.
+    free_text: bool,
     action: fn(&[&str]) -> Option<Action>,
 }
 
@@ -90,7 +141,7 @@ pub fn get_completions(input: &str) -> Vec<Completion> {
                     .last()
                     .and_then(|(i, arg)| {
                         command.args.get(i).map(|completer| {
-                            let head = input.split_whitespace().take(i + 1).collect::<String>();
+                            let head = input.split(' ').take(i + 1).collect::<Vec<_>>().join(" ");
                             completer(arg)
                                 .into_iter()
                                 .map(|c| Completion {
@@ -148,14 +199,36 @@ fn parse_ident(ident: &str) -> Option<&Command> {
 }
 
 pub fn parse_command(input: &str) -> Option<Action> {
-    let mut input = input.split_whitespace();
-
-    input
-        .next()
-        .and_then(|ident| parse_ident(ident))
-        .map(|command| (command, input.collect::<Vec<_>>()))
-        .filter(|(command, args)| args.len() == command.args.len())
-        .and_then(|(command, args)| (command.action)(&args))
+    let (ident, mut rest) = next_word(input);
+    let command = parse_ident(ident)?;
+
+    let words = command.args.len() - usize::from(command.free_text);
+    let mut args = Vec::with_capacity(command.args.len());
+    for _ in 0..words {
+        let (word, after) = next_word(rest);
+        if word.is_empty() {
+            return None;
+        }
+        args.push(word);
+        rest = after;
+    }
+    if command.free_text {
+        args.push(rest.trim_start());
+    } else if !rest.trim().is_empty() {
+        return None;
+    }
+
+    (command.action)(&args)
+}
+
+/// Splits the first word off `input`, returning it and the rest of the input
+/// as it was typed.
+fn next_word(input: &str) -> (&str, &str) {
+    let mut parts = input.trim_start().splitn(2, char::is_whitespace);
+    (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or_default(),
+    )
 }
 
 const COMMANDS: &[Command] = &[
@@ -164,22 +237,145 @@ const COMMANDS: &[Command] = &[
         aliases: &["q"],
         description: "Quit sketshy",
         args: &[],
+        free_text: false,
         action: |_args| -> Option<Action> { Some(Quit) },
     },
+    Command {
+        name: "new",
+        aliases: &["n"],
+        description: "Start a new, empty drawing",
+        args: &[],
+        free_text: false,
+        action: |_args| -> Option<Action> { Some(New) },
+    },
+    Command {
+        name: "open",
+        aliases: &["o"],
+        description: "Open a sketshy file",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(Open(args[0].to_string())) },
+    },
+    Command {
+        name: "save",
+        aliases: &["w"],
+        description: "Save to a sketshy file",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(Save(args[0].to_string())) },
+    },
     Command {
         name: "export",
         aliases: &["e"],
         description: "Export to a plaintext file",
         args: &[completer_path],
+        free_text: true,
         action: |args| -> Option<Action> { Some(Export(args[0].to_string())) },
     },
-    // Command {
-    //     name: "import",
-    //     aliases: &["i"],
-    //     description: "Import from a plaintext file",
-    //     args: &[completer_path],
-    //     action: |args| -> Option<Action> { Some(Import(args[0].to_string())) },
-    // },
+    Command {
+        name: "export-wrapped",
+        aliases: &[],
+        description: "Export to a plaintext file, wrapped in a Markdown fence or comment",
+        args: &[completer_wrap, completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> {
+            Some(ExportWrapped(args[0].parse().ok()?, args[1].to_string()))
+        },
+    },
+    Command {
+        name: "export-ascii",
+        aliases: &[],
+        description: "Export to a plaintext file with only ASCII characters",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(ExportAscii(args[0].to_string())) },
+    },
+    Command {
+        name: "export-selected",
+        aliases: &["es"],
+        description: "Export only the selection to a plaintext file",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(ExportSelected(args[0].to_string())) },
+    },
+    Command {
+        name: "export-svg",
+        aliases: &[],
+        description: "Export to an SVG image of monospace text",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(ExportSvg(args[0].to_string())) },
+    },
+    Command {
+        name: "export-html",
+        aliases: &[],
+        description: "Export to a coloured HTML <pre> block",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(ExportHtml(args[0].to_string())) },
+    },
+    Command {
+        name: "export-ansi",
+        aliases: &[],
+        description: "Export to text coloured with ANSI escape codes",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(ExportAnsi(args[0].to_string())) },
+    },
+    Command {
+        name: "import",
+        aliases: &["i"],
+        description: "Import from a plaintext file",
+        args: &[completer_path],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(Import(args[0].to_string())) },
+    },
+    Command {
+        name: "title",
+        aliases: &[],
+        description: "Set the title of the selected boxes, or clear it if empty",
+        args: &[completer_text],
+        free_text: true,
+        action: |args| -> Option<Action> { Some(SetTitle(args[0].to_string())) },
+    },
+    Command {
+        name: "brush",
+        aliases: &["b"],
+        description: "Pick the glyph the paint tool stamps",
+        args: &[completer_brush],
+        free_text: false,
+        action: |args| -> Option<Action> { args[0].chars().next().map(SetBrush) },
+    },
+    Command {
+        name: "align",
+        aliases: &[],
+        description: "Line up the selected elements by an edge or their center",
+        args: &[completer_align],
+        free_text: false,
+        action: |args| -> Option<Action> { args[0].parse().ok().map(Align) },
+    },
+    Command {
+        name: "distribute",
+        aliases: &[],
+        description: "Space the selected elements out evenly",
+        args: &[completer_axis],
+        free_text: false,
+        action: |args| -> Option<Action> { args[0].parse().ok().map(Distribute) },
+    },
+    Command {
+        name: "grid",
+        aliases: &[],
+        description: "Snap to a grid of the given size, or turn it off with 0",
+        args: &[completer_grid],
+        free_text: false,
+        action: |args| -> Option<Action> {
+            Some(
+                args[0]
+                    .parse()
+                    .map_or_else(|_| Error(format!("Not a grid size: {}", args[0])), SetGrid),
+            )
+        },
+    },
 ];
 
 // impl Command {
@@ -212,3 +408,51 @@ const COMMANDS: &[Command] = &[
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("q"), Some(Quit));
+        assert_eq!(parse_command("quit now"), None);
+        assert_eq!(parse_command("align left right"), None);
+        assert_eq!(
+            parse_command("w  my  drawing.json"),
+            Some(Save("my  drawing.json".into()))
+        );
+        assert_eq!(
+            parse_command("export-wrapped md notes/a b.md"),
+            Some(ExportWrapped(
+                drawing::export::Wrap::Markdown,
+                "notes/a b.md".into()
+            ))
+        );
+        assert_eq!(parse_command("grid"), None);
+        assert_eq!(parse_command("grid 4"), Some(SetGrid(4)));
+        assert_eq!(
+            parse_command("grid x"),
+            Some(Error("Not a grid size: x".into()))
+        );
+        assert_eq!(parse_command("title"), Some(SetTitle("".into())));
+        assert_eq!(
+            parse_command("title Web  server"),
+            Some(SetTitle("Web  server".into()))
+        );
+    }
+
+    #[test]
+    fn test_get_completions() {
+        let fulls = |input| {
+            get_completions(input)
+                .into_iter()
+                .map(|c| c.full)
+                .collect::<Vec<_>>()
+        };
+        assert!(fulls("export-wrapped m").contains(&"export-wrapped markdown".into()));
+        let paths = fulls("export-wrapped md sr");
+        assert!(!paths.is_empty());
+        assert!(paths.iter().all(|p| p.starts_with("export-wrapped md src")));
+    }
+}
@@ -5,7 +5,7 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use layout::Flex;
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Clear, List, ListState},
+    widgets::{Block, Borders, Clear, List, ListState, Paragraph},
 };
 
 use color_eyre::Result;
@@ -19,6 +19,9 @@ pub struct CommandPalette {
     action_tx: Option<UnboundedSender<Action>>,
     completions: Vec<commands::Completion>,
     list_state: ListState,
+    /// The last error, shown along the bottom of the screen until the next
+    /// key press.
+    error: Option<String>,
 }
 
 impl CommandPalette {
@@ -31,6 +34,19 @@ impl CommandPalette {
 
 impl Component for CommandPalette {
     fn draw(&mut self, frame: &mut ratatui::Frame, area: ratatui::prelude::Rect) -> Result<()> {
+        if let Some(error) = self.error.as_ref().filter(|_| !self.active) {
+            let [_, line] =
+                Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+            frame.render_widget(Clear, line);
+            frame.render_widget(
+                Paragraph::new(error.as_str()).style(
+                    Style::new()
+                        .bg(color_scheme::BG_ELEVATED)
+                        .fg(color_scheme::FG_ERROR),
+                ),
+                line,
+            );
+        }
         if self.active {
             let [area] = Layout::horizontal([92]).flex(Flex::Center).areas(area);
             let [area] = Layout::vertical([24]).flex(Flex::Center).areas(area);
@@ -78,6 +94,7 @@ impl Component for CommandPalette {
         Ok(())
     }
     fn handle_key_event(&mut self, key: KeyEvent) -> Result<Option<Action>> {
+        self.error = None;
         if self.active {
             match key {
                 KeyEvent {
@@ -181,6 +198,10 @@ impl Component for CommandPalette {
                 self.completions = commands::get_completions("");
                 Ok(None)
             }
+            Action::Error(error) => {
+                self.error = Some(error);
+                Ok(None)
+            }
             _ => Ok(None),
         }
     }
@@ -1,7 +1,10 @@
-use std::{collections::HashSet, fs::File, io::Write, ops::Not, path::PathBuf};
+use std::{
+    collections::{HashSet, VecDeque},
+    fs::{self, read_to_string},
+};
 
 use color_eyre::Result;
-use crossterm::event::{KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use layout::{Flex, Offset};
 use ratatui::{prelude::*, widgets::*};
 use serde::{Deserialize, Serialize};
@@ -13,27 +16,38 @@ use crate::{
     action::Action,
     app::color_scheme,
     config::Config,
-    drawing::{Direction, DrawingCanvas, Element, Operation, StraightLine},
+    drawing::{
+        buffer_to_string,
+        export::{self, TextOptions, Wrap},
+        import,
+        property::{self, Property},
+        Area, Connector, Direction, Document, DrawingCanvas, Element, ElementId, ElementKind,
+        Endpoint, Guide, History, LineDirection, Operation, Point, Polyline, Side, Snap, Snapshot,
+        StraightLine,
+    },
 };
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Tool {
+    #[default]
     Cursor,
     Box,
+    Ellipse,
+    Diamond,
     Line,
+    Polyline,
     Text,
+    Paint,
+    Eraser,
 }
 
 const LIST_WIDTH: u16 = 12;
 const STYLE_WIDTH: u16 = 20;
+const RULER_WIDTH: u16 = 6;
 
-const SCROLL_STEP: u16 = 4;
+const SCROLL_STEP: i32 = 4;
 
-impl Default for Tool {
-    fn default() -> Self {
-        Self::Cursor
-    }
-}
+const DEFAULT_BRUSH: char = '█';
 
 #[derive(Default)]
 pub struct Home {
@@ -41,9 +55,121 @@ pub struct Home {
     config: Config,
     current_tool: Tool,
     current_operation: Option<Operation>,
-    selected_elements: HashSet<usize>,
+    selected_elements: HashSet<ElementId>,
     canvas: DrawingCanvas,
-    scroll_offset: Position,
+    history: History,
+    /// The canvas as it was before the current paint or eraser stroke, which
+    /// is only recorded if the stroke changes anything.
+    stroke_start: Option<Snapshot>,
+    clipboard: Vec<Element>,
+    mouse_position: Option<Point>,
+    /// The point of the canvas shown in the top left corner of the screen's
+    /// canvas area.
+    viewport: Point,
+    canvas_area: Rect,
+    rulers: bool,
+    top_ruler: Rect,
+    left_ruler: Rect,
+    /// How many cells apart the lines of the snapping grid are.
+    grid: u16,
+    layers_focused: bool,
+    layers_state: ListState,
+    layer_cursor: Option<ElementId>,
+    layer_drag: Option<ElementId>,
+    expanded_groups: HashSet<ElementId>,
+    brush: Option<char>,
+    style_area: Rect,
+    style_focused: bool,
+    style_cursor: Option<Property>,
+    property_input: Option<TextArea<'static>>,
+}
+
+/// Returns every cell on the way from `from` to `to`, so that fast mouse
+/// movements don't leave gaps in a stroke.
+fn cells_between(from: Point, to: Point) -> Vec<Point> {
+    let (dx, dy) = (to.x - from.x, -(to.y - from.y).abs());
+    let (step_x, step_y) = (dx.signum(), (to.y - from.y).signum());
+    let dx = dx.abs();
+
+    let (mut x, mut y) = (from.x, from.y);
+    let mut error = dx + dy;
+    let mut cells = vec![];
+    loop {
+        cells.push(Point::new(x, y));
+        if x == to.x && y == to.y {
+            return cells;
+        }
+        if 2 * error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if 2 * error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+}
+
+/// The cells just beyond either end of a line, which drag its first and its
+/// second end around.
+fn line_handles(line: &StraightLine) -> [Point; 2] {
+    use LineDirection::*;
+
+    let (dx, dy) = match line.direction {
+        Right => (1, 0),
+        DownRight => (2, 1),
+        Down => (0, 1),
+        DownLeft => (-2, 1),
+        Left => (-1, 0),
+        UpLeft => (-2, -1),
+        Up => (0, -1),
+        UpRight => (2, -1),
+    };
+    [
+        Point::new(line.from.x - dx, line.from.y - dy),
+        Point::new(line.to.x + dx, line.to.y + dy),
+    ]
+}
+
+/// A row of the layers panel.
+struct LayerRow<'a> {
+    /// How deeply nested in groups the element is.
+    depth: usize,
+    /// The top-level element the row belongs to, which is what gets selected
+    /// when clicking it.
+    layer: ElementId,
+    element: &'a Element,
+}
+
+/// Flattens the layer tree into the rows of the layers panel, top to bottom.
+fn layer_rows<'a>(
+    elements: &'a VecDeque<Element>,
+    expanded_groups: &HashSet<ElementId>,
+) -> Vec<LayerRow<'a>> {
+    fn push_rows<'a>(
+        rows: &mut Vec<LayerRow<'a>>,
+        element: &'a Element,
+        depth: usize,
+        layer: ElementId,
+        expanded_groups: &HashSet<ElementId>,
+    ) {
+        rows.push(LayerRow {
+            depth,
+            layer,
+            element,
+        });
+        if expanded_groups.contains(&element.id) {
+            for child in element.kind.children().iter().rev() {
+                push_rows(rows, child, depth + 1, layer, expanded_groups);
+            }
+        }
+    }
+
+    let mut rows = vec![];
+    for element in elements.iter().rev() {
+        push_rows(&mut rows, element, 0, element.id, expanded_groups);
+    }
+    rows
 }
 
 impl Home {
@@ -67,6 +193,459 @@ impl Home {
         self.update_tool(Tool::Cursor);
         self.selected_elements.clear();
     }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            elements: self.canvas.elements.clone(),
+            guides: self.canvas.guides.clone(),
+            selected_elements: self.selected_elements.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.canvas.elements = snapshot.elements;
+        self.canvas.guides = snapshot.guides;
+        self.selected_elements = snapshot.selected_elements;
+        self.current_operation = None;
+    }
+
+    /// Clones the selected elements, in layer order.
+    fn copy_selection(&self) -> Vec<Element> {
+        self.canvas
+            .iter_selected(&self.selected_elements)
+            .cloned()
+            .collect()
+    }
+
+    /// Adds new elements on top of the canvas and selects them.
+    fn insert_kinds(&mut self, kinds: Vec<ElementKind>) {
+        self.checkpoint();
+        self.reset_tool();
+        for kind in kinds {
+            let id = self.canvas.push(kind);
+            self.selected_elements.insert(id);
+        }
+    }
+
+    /// Adds copies of `elements` on top of the canvas, moved by `offset`, and
+    /// selects them.
+    fn insert_elements(&mut self, elements: Vec<Element>, offset: Offset) {
+        self.checkpoint();
+        self.reset_tool();
+        self.selected_elements
+            .extend(self.canvas.insert(elements, offset));
+    }
+
+    /// The end of a connector at `position`. It's bound to the topmost
+    /// element there, on the side facing `towards`, unless there's nothing to
+    /// connect to.
+    fn endpoint_at(&self, position: Point, towards: Point) -> Endpoint {
+        self.canvas
+            .elements
+            .iter()
+            .rev()
+            .filter(|el| {
+                !matches!(
+                    el.kind,
+                    ElementKind::Line(..) | ElementKind::Polyline(..) | ElementKind::Connector(..)
+                )
+            })
+            .find(|el| el.area().contains(position))
+            .map_or(Endpoint::Free { position }, |el| Endpoint::Bound {
+                element: el.id,
+                side: Side::facing(el.area(), towards),
+            })
+    }
+
+    /// Returns the only selected element, if exactly one is selected.
+    fn single_selection(&self) -> Option<&Element> {
+        if self.selected_elements.len() == 1 {
+            self.selected_elements
+                .iter()
+                .next()
+                .and_then(|id| self.canvas.get(*id))
+        } else {
+            None
+        }
+    }
+
+    fn send(&self, action: Action) {
+        if let Some(tx) = &self.command_tx {
+            let _ = tx.send(action);
+        }
+    }
+
+    /// Returns the row of the layers panel at screen row `row`.
+    fn layer_at(&self, row: u16) -> Option<LayerRow<'_>> {
+        let list_row = (row as usize).checked_sub(1)? + self.layers_state.offset();
+        layer_rows(&self.canvas.elements, &self.expanded_groups)
+            .into_iter()
+            .nth(list_row)
+    }
+
+    /// Moves the layers panel cursor by `by` layers, either replacing the
+    /// selection with the layer under the cursor or adding it.
+    fn move_layer_cursor(&mut self, by: isize, extend: bool) {
+        let Some(top) = self.canvas.elements.len().checked_sub(1) else {
+            return;
+        };
+
+        let index = match self.layer_cursor.and_then(|id| self.canvas.index_of(id)) {
+            Some(index) => index.saturating_add_signed(by).min(top),
+            None => top,
+        };
+        let cursor = self.canvas.elements[index].id;
+
+        if !extend {
+            self.selected_elements.clear();
+        }
+        self.selected_elements.insert(cursor);
+        self.layer_cursor = Some(cursor);
+    }
+
+    fn handle_layers_mouse_event(&mut self, mouse: MouseEvent) -> Option<Action> {
+        let (layer, group_arrow) = match self.layer_at(mouse.row) {
+            Some(row) => (
+                Some(row.layer),
+                matches!(row.element.kind, ElementKind::Group { .. })
+                    .then_some((row.element.id, 1 + row.depth as u16 * 2)),
+            ),
+            None => (None, None),
+        };
+        let alt = mouse.modifiers.contains(KeyModifiers::ALT);
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if !self.layers_focused {
+                    self.send(Action::FocusLayers);
+                }
+                self.update_tool(Tool::Cursor);
+
+                // Clicking a group's arrow folds it open or closed
+                if let Some((group, _)) = group_arrow.filter(|(_, x)| *x == mouse.column) {
+                    if !self.expanded_groups.remove(&group) {
+                        self.expanded_groups.insert(group);
+                    }
+                    return Some(Action::RenderBuffer);
+                }
+
+                match layer {
+                    Some(id) if alt => {
+                        if !self.selected_elements.remove(&id) {
+                            self.selected_elements.insert(id);
+                        }
+                    }
+                    Some(id) if !self.selected_elements.contains(&id) => {
+                        self.selected_elements.clear();
+                        self.selected_elements.insert(id);
+                    }
+                    Some(_) => {}
+                    None if alt => {}
+                    None => self.selected_elements.clear(),
+                }
+
+                self.layer_cursor = layer.or(self.layer_cursor);
+                self.layer_drag = layer;
+                Some(Action::RenderBuffer)
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                match (self.layer_drag.take(), layer) {
+                    (Some(origin), Some(target))
+                        if origin != target && self.selected_elements.contains(&origin) =>
+                    {
+                        self.checkpoint();
+                        self.canvas.move_layers(&self.selected_elements, target);
+                        self.layer_cursor = Some(origin);
+                    }
+                    (Some(origin), Some(target)) if origin == target && !alt => {
+                        self.selected_elements.clear();
+                        self.selected_elements.insert(target);
+                    }
+                    _ => {}
+                }
+                Some(Action::RenderBuffer)
+            }
+            _ => None,
+        }
+    }
+
+    /// The point of the canvas under the cell at `column` and `row` of the
+    /// screen.
+    fn canvas_point(&self, column: u16, row: u16) -> Point {
+        Point::new(
+            self.viewport.x + column as i32 - self.canvas_area.x as i32,
+            self.viewport.y + row as i32 - self.canvas_area.y as i32,
+        )
+    }
+
+    /// Where edges snap to while editing the selection: guides, the edges of
+    /// the other elements and the grid. Connectors follow whatever they're
+    /// bound to, so they're left out. Holding Alt turns snapping off.
+    fn snap(&self, modifiers: KeyModifiers) -> Snap {
+        if modifiers.contains(KeyModifiers::ALT) {
+            return Snap::default();
+        }
+        Snap::new(
+            self.grid,
+            &self.canvas.guides,
+            self.canvas
+                .elements
+                .iter()
+                .filter(|el| {
+                    !self.selected_elements.contains(&el.id)
+                        && !matches!(el.kind, ElementKind::Connector(..))
+                })
+                .map(|el| el.area()),
+        )
+    }
+
+    /// The bounding box of the selection, which the resize handles go
+    /// around. Connectors don't count, as they follow what they're bound to,
+    /// and single lines and polylines have handles of their own instead.
+    fn resize_bounds(&self) -> Option<Area> {
+        if self.single_selection().is_some_and(|el| {
+            matches!(
+                el.kind,
+                ElementKind::Line(..) | ElementKind::Polyline(..) | ElementKind::Connector(..)
+            )
+        }) {
+            return None;
+        }
+        self.canvas
+            .iter_selected(&self.selected_elements)
+            .filter(|el| !matches!(el.kind, ElementKind::Connector(..)))
+            .map(|el| el.area())
+            .reduce(Area::union)
+    }
+
+    /// The guide running through `point`, unless it's hidden behind an
+    /// element there.
+    fn guide_at(&self, point: Point) -> Option<usize> {
+        if self
+            .canvas
+            .elements
+            .iter()
+            .any(|el| el.area().contains(point))
+        {
+            return None;
+        }
+        self.canvas
+            .guides
+            .iter()
+            .position(|guide| guide.contains(point))
+    }
+
+    /// Writes a plaintext export to `path`, wrapped in `wrap` and laid out as
+    /// configured.
+    fn export_text(&self, path: &str, text: &str, wrap: Wrap) -> Option<Action> {
+        let options = TextOptions {
+            wrap,
+            ..self.config.config.text_export.clone()
+        };
+        write_export(path, &export::wrap_text(text, &options))
+    }
+
+    /// Renders the part of the canvas in view.
+    fn render_canvas(&mut self) {
+        let viewport = Area::new(
+            self.viewport.x,
+            self.viewport.y,
+            self.canvas_area.width,
+            self.canvas_area.height,
+        );
+        self.canvas
+            .render(&self.selected_elements, &self.current_operation, viewport);
+    }
+
+    /// The whole drawing as exported, without the colours of the selection.
+    fn render_unselected(&self) -> Buffer {
+        let all = self.canvas.elements.iter().map(|el| el.id).collect();
+        self.canvas.render_selection(&all)
+    }
+
+    /// Records the current state in the undo history. Call this right before
+    /// mutating the canvas.
+    fn checkpoint(&mut self) {
+        self.history.record(self.snapshot());
+    }
+
+    /// Records the canvas from before the current stroke, if the stroke
+    /// changed it.
+    fn finish_stroke(&mut self) {
+        if let Some(start) = self
+            .stroke_start
+            .take()
+            .filter(|start| start.elements != self.canvas.elements)
+        {
+            self.history.record(start);
+        }
+    }
+
+    fn selected_kinds(&self) -> Vec<&ElementKind> {
+        self.canvas
+            .iter_selected(&self.selected_elements)
+            .map(|el| &el.kind)
+            .collect()
+    }
+
+    /// Every property listed in the style editor, top to bottom.
+    fn style_properties(&self) -> Vec<Property> {
+        property::sections(&self.selected_kinds())
+            .into_iter()
+            .flat_map(|(_, properties)| properties)
+            .collect()
+    }
+
+    /// The property under the style editor's cursor, which is the first one
+    /// if the cursor's property isn't listed anymore.
+    fn current_property(&self) -> Option<Property> {
+        let properties = self.style_properties();
+        self.style_cursor
+            .filter(|cursor| properties.contains(cursor))
+            .or(properties.first().copied())
+    }
+
+    fn move_style_cursor(&mut self, by: isize) {
+        let properties = self.style_properties();
+        if let Some(index) = self
+            .current_property()
+            .and_then(|current| properties.iter().position(|p| *p == current))
+        {
+            let index = (index as isize - by).clamp(0, properties.len() as isize - 1);
+            self.style_cursor = Some(properties[index as usize]);
+        }
+    }
+
+    /// Applies `change` to every selected element as a single undo step.
+    fn change_selection(
+        &mut self,
+        change: impl Fn(&mut ElementKind) -> std::result::Result<(), String>,
+    ) -> Option<Action> {
+        let mut changed = vec![];
+        for el in self.canvas.iter_selected(&self.selected_elements) {
+            let mut kind = el.kind.clone();
+            if let Err(err) = change(&mut kind) {
+                return Some(Action::Error(err));
+            }
+            if kind != el.kind {
+                changed.push((el.id, kind));
+            }
+        }
+        if changed.is_empty() {
+            return None;
+        }
+
+        self.checkpoint();
+        for (id, kind) in changed {
+            if let Some(el) = self.canvas.get_mut(id) {
+                el.kind = kind;
+            }
+        }
+        Some(Action::RenderBuffer)
+    }
+
+    /// Picks the next option of the current property, or starts typing in a
+    /// new value for it.
+    fn activate_property(&mut self) -> Option<Action> {
+        let property = self.current_property()?;
+        if self
+            .selected_kinds()
+            .iter()
+            .all(|kind| property.is_read_only(kind))
+        {
+            return None;
+        }
+        if !property.is_typed() {
+            return self.change_selection(|kind| {
+                property.step(kind, 1);
+                Ok(())
+            });
+        }
+
+        let value = property::common_value(property, &self.selected_kinds()).unwrap_or_default();
+        let mut textarea = TextArea::from([value]);
+        textarea.set_style(Style::new().fg(color_scheme::FG_BASE));
+        textarea.set_cursor_line_style(Style::new());
+        textarea.move_cursor(tui_textarea::CursorMove::End);
+        self.property_input = Some(textarea);
+        Some(Action::EditProperty)
+    }
+
+    /// Where the sections of the style editor go, and which properties they
+    /// list.
+    fn style_sections(&self) -> Vec<(Rect, &'static str, Vec<Property>)> {
+        let sections = property::sections(&self.selected_kinds());
+        let areas = Layout::vertical(
+            sections
+                .iter()
+                .map(|(_, properties)| Constraint::Length(properties.len() as u16 + 2)),
+        )
+        .split(self.style_area);
+
+        sections
+            .into_iter()
+            .zip(areas.iter())
+            .map(|((title, properties), area)| (*area, title, properties))
+            .collect()
+    }
+
+    /// Clicking a property in the style editor focuses it and picks its next
+    /// option, or starts typing in a new value.
+    fn handle_style_mouse_event(&mut self, mouse: MouseEvent) -> Option<Action> {
+        let position = Position::new(mouse.column, mouse.row);
+        let property = self
+            .style_sections()
+            .into_iter()
+            .find_map(|(area, _, properties)| {
+                let area = area.inner(Margin::new(1, 1));
+                area.contains(position)
+                    .then(|| properties.get((position.y - area.y) as usize).copied())
+                    .flatten()
+            })?;
+
+        if !self.style_focused {
+            self.send(Action::FocusStyle);
+        }
+        self.style_cursor = Some(property);
+        self.activate_property()
+    }
+
+    /// The index of the vertex at `position`, if a single polyline is
+    /// selected and has one there.
+    fn vertex_at(&self, position: Point) -> Option<usize> {
+        match self.single_selection().map(|el| &el.kind) {
+            Some(ElementKind::Polyline(polyline)) => {
+                polyline.points.iter().position(|point| *point == position)
+            }
+            _ => None,
+        }
+    }
+
+    /// Paints or erases every cell from `from` to `to`, depending on the
+    /// current tool.
+    fn stroke(&mut self, from: Point, to: Point) {
+        let brush = self.brush.unwrap_or(DEFAULT_BRUSH);
+        for position in cells_between(from, to) {
+            match self.current_tool {
+                Tool::Paint => {
+                    if let Some(el) = self
+                        .selected_elements
+                        .iter()
+                        .next()
+                        .and_then(|id| self.canvas.get_mut(*id))
+                    {
+                        el.kind.paint(position, brush);
+                    }
+                }
+                Tool::Eraser => {
+                    for el in self.canvas.elements.iter_mut() {
+                        el.kind.erase(position);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
 }
 
 impl Component for Home {
@@ -76,6 +655,8 @@ impl Component for Home {
     }
 
     fn register_config_handler(&mut self, config: Config) -> Result<()> {
+        self.grid = config.config.grid_size;
+        self.rulers = config.config.rulers;
         self.config = config;
         Ok(())
     }
@@ -84,6 +665,11 @@ impl Component for Home {
         if let Some(Operation::EditText { textarea }) = &mut self.current_operation {
             textarea.input(key);
         }
+        if let Some(textarea) = &mut self.property_input {
+            if key.code != KeyCode::Enter {
+                textarea.input(key);
+            }
+        }
         Ok(None)
     }
 
@@ -95,239 +681,319 @@ impl Component for Home {
             modifiers,
         } = mouse;
 
-        if column < LIST_WIDTH {
+        // Guides keep following the mouse across the panels
+        if let Some(Operation::MoveGuide { index }) = self.current_operation {
+            match kind {
+                MouseEventKind::Drag(MouseButton::Left) => {
+                    let point = self.canvas_point(column, row);
+                    if let Some(guide) = self.canvas.guides.get_mut(index) {
+                        guide.move_to(point);
+                    }
+                }
+                MouseEventKind::Up(MouseButton::Left) => {
+                    // Dropping a guide off the canvas removes it
+                    if !self.canvas_area.contains(Position::new(column, row))
+                        && index < self.canvas.guides.len()
+                    {
+                        self.canvas.guides.remove(index);
+                    }
+                    self.current_operation = None;
+                }
+                _ => (),
+            }
             return Ok(None);
         }
 
+        if column < LIST_WIDTH {
+            return Ok(self.handle_layers_mouse_event(mouse));
+        }
+
+        // Drags that started on the canvas keep going when crossing the style
+        // editor, so only clicks are handled there
+        if kind == MouseEventKind::Down(MouseButton::Left)
+            && self.style_area.contains(Position::new(column, row))
+        {
+            return Ok(self.handle_style_mouse_event(mouse));
+        }
+
+        let point = self.canvas_point(column, row);
+        self.mouse_position = Some(point);
+
+        // Dragging out of a ruler adds a guide along it
+        if kind == MouseEventKind::Down(MouseButton::Left) {
+            let position = Position::new(column, row);
+            let guide = if self.top_ruler.contains(position) {
+                Some(Guide::Horizontal { y: point.y })
+            } else if self.left_ruler.contains(position) {
+                Some(Guide::Vertical { x: point.x })
+            } else {
+                None
+            };
+            if let Some(guide) = guide {
+                self.checkpoint();
+                self.canvas.guides.push(guide);
+                self.current_operation = Some(Operation::MoveGuide {
+                    index: self.canvas.guides.len() - 1,
+                });
+                return Ok(None);
+            }
+        }
+
         match kind {
             MouseEventKind::Down(MouseButton::Left) => {
-                let row = row + self.scroll_offset.y;
-                let column = column - LIST_WIDTH + self.scroll_offset.x;
+                if self.layers_focused || self.style_focused {
+                    self.send(Action::FocusCanvas);
+                }
                 match self.current_tool {
                     Tool::Cursor => {
-                        if self
-                            .selected_elements
-                            .iter()
-                            .flat_map(|i| self.canvas.elements.get(*i))
-                            .any(|el| el.area().contains(Position { x: column, y: row }))
+                        if let Some(index) = self.vertex_at(point) {
+                            self.current_operation =
+                                Some(Operation::MoveVertex { index, pos: point });
+                            Ok(Some(Action::RenderBuffer))
+                        } else if self
+                            .canvas
+                            .iter_selected(&self.selected_elements)
+                            .any(|el| el.area().contains(point))
                         {
                             self.current_operation = Some(Operation::Move {
-                                origin: Position { x: column, y: row },
-                                second: Position { x: column, y: row },
+                                origin: point,
+                                second: point,
                             });
                             Ok(None)
                         } else {
-                            if self.selected_elements.len() == 1 {
-                                if let Some(Element::Line(line)) = self
-                                    .selected_elements
-                                    .iter()
-                                    .next()
-                                    .and_then(|i| self.canvas.elements.get(*i))
-                                {
-                                    use crate::drawing::elements::LineDirection::*;
-
-                                    let handles = match &line.direction {
-                                        Right => [
-                                            (line.from.x as i16 - 1, line.from.y as i16),
-                                            (line.to.x as i16 + 1, line.to.y as i16),
-                                        ],
-                                        DownRight => [
-                                            (line.from.x as i16 - 2, line.from.y as i16 - 1),
-                                            (line.to.x as i16 + 2, line.to.y as i16 + 1),
-                                        ],
-                                        Down => [
-                                            (line.from.x as i16, line.from.y as i16 - 1),
-                                            (line.to.x as i16, line.to.y as i16 + 1),
-                                        ],
-                                        DownLeft => [
-                                            (line.from.x as i16 + 2, line.from.y as i16 - 1),
-                                            (line.to.x as i16 - 2, line.to.y as i16 + 1),
-                                        ],
-                                        Left => [
-                                            (line.from.x as i16 + 1, line.from.y as i16),
-                                            (line.to.x as i16 - 1, line.to.y as i16),
-                                        ],
-                                        UpLeft => [
-                                            (line.from.x as i16 + 2, line.from.y as i16 + 1),
-                                            (line.to.x as i16 - 2, line.to.y as i16 - 1),
-                                        ],
-                                        Up => [
-                                            (line.from.x as i16, line.from.y as i16 + 1),
-                                            (line.to.x as i16, line.to.y as i16 - 1),
-                                        ],
-                                        UpRight => [
-                                            (line.from.x as i16 - 2, line.from.y as i16 + 1),
-                                            (line.to.x as i16 + 2, line.to.y as i16 - 1),
-                                        ],
-                                    };
-
-                                    if handles[0].0 + self.scroll_offset.x as i16 == column as i16
-                                        && handles[0].1 + self.scroll_offset.y as i16 == row as i16
-                                    {
-                                        self.current_operation = Some(Operation::MoveLineHandle {
-                                            handle: crate::drawing::LineHandle::First,
-                                            pos: (column, row).into(),
-                                        });
-                                        return Ok(Some(Action::RenderBuffer));
-                                    } else if handles[1].0 + self.scroll_offset.x as i16
-                                        == column as i16
-                                        && handles[1].1 + self.scroll_offset.y as i16 == row as i16
-                                    {
-                                        self.current_operation = Some(Operation::MoveLineHandle {
-                                            handle: crate::drawing::LineHandle::Second,
-                                            pos: (column, row).into(),
-                                        });
-                                        return Ok(Some(Action::RenderBuffer));
-                                    }
-                                } else if let Some(direction) = self
-                                    .selected_elements
-                                    .iter()
-                                    .filter_map(|i| {
-                                        self.canvas.elements.get(*i).and_then(|el| {
-                                            let area = el.area();
-
-                                            if area.x.saturating_sub(1) == column
-                                                && area.y.saturating_sub(1) == row
-                                            {
-                                                Some(Direction::TopLeft)
-                                            } else if area.x + area.width == column
-                                                && area.y.saturating_sub(1) == row
-                                            {
-                                                Some(Direction::TopRight)
-                                            } else if area.x.saturating_sub(1) == column
-                                                && area.y + area.height == row
-                                            {
-                                                Some(Direction::BottomLeft)
-                                            } else if area.x + area.width == column
-                                                && area.y + area.height == row
-                                            {
-                                                Some(Direction::BottomRight)
-                                            } else {
-                                                None
-                                            }
-                                        })
-                                    })
-                                    .next()
-                                {
-                                    self.current_operation = Some(Operation::Resize {
-                                        direction,
-                                        origin: Position { x: column, y: row },
-                                        second: Position { x: column, y: row },
-                                    });
+                            if let Some(ElementKind::Line(line)) =
+                                self.single_selection().map(|el| &el.kind)
+                            {
+                                let [first, second] = line_handles(line);
+                                let handle = if point == first {
+                                    Some(crate::drawing::LineHandle::First)
+                                } else if point == second {
+                                    Some(crate::drawing::LineHandle::Second)
+                                } else {
+                                    None
+                                };
+                                if let Some(handle) = handle {
+                                    self.current_operation =
+                                        Some(Operation::MoveLineHandle { handle, pos: point });
                                     return Ok(Some(Action::RenderBuffer));
                                 }
                             }
+                            if let Some((bounds, direction)) =
+                                self.resize_bounds().and_then(|bounds| {
+                                    Direction::of_handle(bounds, point)
+                                        .map(|direction| (bounds, direction))
+                                })
+                            {
+                                self.current_operation = Some(Operation::Resize {
+                                    direction,
+                                    origin: point,
+                                    second: point,
+                                    bounds,
+                                    proportional: modifiers.contains(KeyModifiers::CONTROL),
+                                    centered: modifiers.contains(KeyModifiers::SHIFT),
+                                });
+                                return Ok(Some(Action::RenderBuffer));
+                            }
+                            if let Some(index) = self.guide_at(point) {
+                                self.checkpoint();
+                                self.current_operation = Some(Operation::MoveGuide { index });
+                                return Ok(None);
+                            }
                             self.current_operation = Some(Operation::Selection {
-                                origin: Position { x: column, y: row },
-                                second: Position { x: column, y: row },
+                                origin: point,
+                                second: point,
                             });
 
                             if modifiers & KeyModifiers::ALT != KeyModifiers::ALT {
                                 self.selected_elements.clear();
                             }
 
-                            if let Some(x) = self
+                            if let Some(el) = self
                                 .canvas
                                 .elements
                                 .iter()
-                                .enumerate()
                                 .rev()
-                                .filter_map(|(i, el)| {
-                                    el.area()
-                                        .contains(Position { x: column, y: row })
-                                        .then_some(i)
-                                })
-                                .next()
+                                .find(|el| el.area().contains(point))
                             {
-                                self.selected_elements.insert(x);
+                                self.selected_elements.insert(el.id);
                             }
 
                             Ok(Some(Action::RenderBuffer))
                         }
                     }
-                    Tool::Box | Tool::Line => {
+                    Tool::Box | Tool::Ellipse | Tool::Diamond | Tool::Line => {
                         self.selected_elements.clear();
+                        let point = self.snap(modifiers).point(point);
                         self.current_operation = Some(Operation::Selection {
-                            origin: (column, row).into(),
-                            second: (column, row).into(),
+                            origin: point,
+                            second: point,
                         });
                         Ok(None)
                     }
+                    Tool::Polyline => {
+                        match &mut self.current_operation {
+                            // Clicking the last vertex again finishes the polyline
+                            Some(Operation::DrawPolyline { points, .. })
+                                if points.last() == Some(&point) =>
+                            {
+                                return Ok(Some(Action::FinishPolyline));
+                            }
+                            Some(Operation::DrawPolyline { points, cursor }) => {
+                                points.push(point);
+                                *cursor = point;
+                            }
+                            _ => {
+                                self.selected_elements.clear();
+                                self.current_operation = Some(Operation::DrawPolyline {
+                                    points: vec![point],
+                                    cursor: point,
+                                });
+                            }
+                        }
+                        Ok(Some(Action::RenderBuffer))
+                    }
+                    Tool::Paint | Tool::Eraser => {
+                        self.stroke_start = Some(self.snapshot());
+                        if self.current_tool == Tool::Paint
+                            && !matches!(
+                                self.single_selection().map(|el| &el.kind),
+                                Some(ElementKind::Paint { .. })
+                            )
+                        {
+                            let id = self.canvas.push(ElementKind::Paint { pixels: vec![] });
+                            self.selected_elements.clear();
+                            self.selected_elements.insert(id);
+                        }
+                        self.stroke(point, point);
+                        self.current_operation = Some(Operation::Stroke { last: point });
+                        Ok(Some(Action::RenderBuffer))
+                    }
                     Tool::Text => {
                         if let Some(Operation::EditText { .. }) = &self.current_operation {
                             Ok(Some(Action::CommitText))
                         } else {
                             self.selected_elements.clear();
                             self.current_operation = Some(Operation::Selection {
-                                origin: (column, row).into(),
-                                second: (column, row).into(),
+                                origin: point,
+                                second: point,
                             });
                             Ok(None)
                         }
                     }
                 }
             }
-            MouseEventKind::Drag(MouseButton::Left) => {
-                let row = row + self.scroll_offset.y;
-                let column = column - LIST_WIDTH + self.scroll_offset.x;
-                match self.current_tool {
-                    Tool::Box | Tool::Text | Tool::Line => {
-                        if let Some(Operation::Selection { origin: _, second }) =
-                            &mut self.current_operation
-                        {
-                            second.x = column;
-                            second.y = row;
-                        }
-                        Ok(Some(Action::RenderBuffer))
+            MouseEventKind::Drag(MouseButton::Left) => match self.current_tool {
+                Tool::Box | Tool::Ellipse | Tool::Diamond | Tool::Text | Tool::Line => {
+                    // Text is placed where it's clicked, so editing existing
+                    // text puts the cursor right there
+                    let point = if self.current_tool == Tool::Text {
+                        point
+                    } else {
+                        self.snap(modifiers).point(point)
+                    };
+                    if let Some(Operation::Selection { origin: _, second }) =
+                        &mut self.current_operation
+                    {
+                        *second = point;
                     }
-                    Tool::Cursor => match &mut self.current_operation {
+                    Ok(Some(Action::RenderBuffer))
+                }
+                Tool::Cursor => {
+                    let snap = self.snap(modifiers);
+                    let selected_area = self
+                        .canvas
+                        .iter_selected(&self.selected_elements)
+                        .map(|el| el.area())
+                        .reduce(Area::union);
+                    match &mut self.current_operation {
                         Some(Operation::Selection { origin, second }) => {
-                            second.x = column;
-                            second.y = row;
-
-                            let area = Rect {
-                                x: origin.x.min(second.x),
-                                y: origin.y.min(second.y),
-                                width: origin.x.abs_diff(second.x) + 1,
-                                height: origin.y.abs_diff(second.y) + 1,
-                            };
+                            *second = point;
+
+                            let area = Area::between(*origin, *second);
 
                             self.selected_elements = self
                                 .canvas
                                 .elements
                                 .iter()
-                                .enumerate()
-                                .filter_map(|(i, el)| el.area().intersects(area).then_some(i))
+                                .filter(|el| el.area().intersects(area))
+                                .map(|el| el.id)
                                 .collect::<HashSet<_>>();
 
                             Ok(Some(Action::RenderBuffer))
                         }
-                        Some(Operation::Move { second: pos, .. })
-                        | Some(Operation::Resize { second: pos, .. })
-                        | Some(Operation::MoveLineHandle { pos, .. }) => {
-                            pos.x = column;
-                            pos.y = row;
+                        Some(Operation::Move { origin, second }) => {
+                            let by = point.offset_from(*origin);
+                            let snapped = selected_area
+                                .map(|area| snap.offset(area.offset(by)))
+                                .unwrap_or_default();
+                            *second = point.offset(snapped);
+                            Ok(Some(Action::RenderBuffer))
+                        }
+                        // It's the corner next to the handle that snaps
+                        Some(Operation::Resize {
+                            direction,
+                            second,
+                            proportional,
+                            centered,
+                            ..
+                        }) => {
+                            *proportional = modifiers.contains(KeyModifiers::CONTROL);
+                            *centered = modifiers.contains(KeyModifiers::SHIFT);
+                            let inward = direction.inward();
+                            *second = snap.point(point.offset(inward)).offset(Offset {
+                                x: -inward.x,
+                                y: -inward.y,
+                            });
+                            Ok(Some(Action::RenderBuffer))
+                        }
+                        Some(Operation::MoveLineHandle { pos, .. })
+                        | Some(Operation::MoveVertex { pos, .. }) => {
+                            *pos = point;
                             Ok(Some(Action::RenderBuffer))
                         }
                         _ => Ok(None),
-                    },
+                    }
+                }
+                Tool::Polyline => {
+                    if let Some(Operation::DrawPolyline { cursor, .. }) =
+                        &mut self.current_operation
+                    {
+                        *cursor = point;
+                    }
+                    Ok(None)
+                }
+                Tool::Paint | Tool::Eraser => {
+                    if let Some(Operation::Stroke { last }) = self.current_operation {
+                        self.stroke(last, point);
+                        self.current_operation = Some(Operation::Stroke { last: point });
+                    }
+                    Ok(Some(Action::RenderBuffer))
                 }
+            },
+            MouseEventKind::Moved => {
+                if let Some(Operation::DrawPolyline { cursor, .. }) = &mut self.current_operation {
+                    *cursor = point;
+                }
+                Ok(None)
             }
             MouseEventKind::Up(MouseButton::Left) => match self.current_tool {
-                Tool::Box => {
+                Tool::Polyline => Ok(None),
+                Tool::Box | Tool::Ellipse | Tool::Diamond => {
                     if let Some(Operation::Selection { origin, second }) = self.current_operation {
-                        let area = Rect {
-                            x: origin.x.min(second.x),
-                            y: origin.y.min(second.y),
-                            width: origin.x.abs_diff(second.x) + 1,
-                            height: origin.y.abs_diff(second.y) + 1,
-                        };
+                        let area = Area::between(origin, second);
 
                         if area.width > 1 && area.height > 1 {
-                            self.canvas.elements.push_back(Element::Box { area });
+                            self.checkpoint();
+                            let id = self.canvas.push(match self.current_tool {
+                                Tool::Ellipse => ElementKind::Ellipse { area },
+                                Tool::Diamond => ElementKind::Diamond { area },
+                                _ => ElementKind::Box {
+                                    area,
+                                    style: Default::default(),
+                                },
+                            });
                             self.reset_tool();
-                            self.selected_elements
-                                .insert(self.canvas.elements.len() - 1);
+                            self.selected_elements.insert(id);
                         }
                     }
                     self.current_operation = None;
@@ -336,12 +1002,27 @@ impl Component for Home {
                 Tool::Line => {
                     if let Some(Operation::Selection { origin, second }) = self.current_operation {
                         if origin != second {
-                            if let Some(line) = StraightLine::new(origin, second) {
-                                self.canvas.elements.push_back(Element::Line(line));
-                            }
                             self.reset_tool();
-                            self.selected_elements
-                                .insert(self.canvas.elements.len() - 1);
+                            let from = self.endpoint_at(origin, second);
+                            let to = match self.endpoint_at(second, origin) {
+                                to if to.bound_to() == from.bound_to() => {
+                                    Endpoint::Free { position: second }
+                                }
+                                to => to,
+                            };
+
+                            // Lines starting or ending on an element connect to it
+                            if from.bound_to().is_some() || to.bound_to().is_some() {
+                                self.checkpoint();
+                                let id = self
+                                    .canvas
+                                    .push(ElementKind::Connector(Connector::new(from, to)));
+                                self.selected_elements.insert(id);
+                            } else if let Some(line) = StraightLine::new(origin, second) {
+                                self.checkpoint();
+                                let id = self.canvas.push(ElementKind::Line(line));
+                                self.selected_elements.insert(id);
+                            }
                         }
                     }
                     self.current_operation = None;
@@ -349,51 +1030,47 @@ impl Component for Home {
                 }
                 Tool::Text => {
                     if let Some(Operation::Selection { origin, second }) = self.current_operation {
-                        let area = Rect {
-                            x: origin.x.min(second.x),
-                            y: origin.y.min(second.y),
-                            width: origin.x.abs_diff(second.x) + 1,
-                            height: origin.y.abs_diff(second.y) + 1,
-                        };
+                        let area = Area::between(origin, second);
 
-                        if let Some((i, Element::Text { content, area })) = self
+                        if let Some((id, ElementKind::Text { content, area, .. })) = self
                             .canvas
                             .elements
                             .iter()
-                            .enumerate()
-                            .filter(|(_, el)| matches!(el, Element::Text { .. }))
-                            .find(|(_, el)| {
-                                el.area().contains(Position {
-                                    x: area.x,
-                                    y: area.y,
-                                })
-                            })
+                            .filter(|el| matches!(el.kind, ElementKind::Text { .. }))
+                            .find(|el| el.area().contains(area.as_point()))
                             .filter(|_| area.area() == 1)
+                            .map(|el| (el.id, &el.kind))
                         {
-                            self.selected_elements.clear();
-                            self.selected_elements.insert(i);
                             let mut textarea = TextArea::from(content.split('\n'));
                             textarea.set_block(
                                 Block::new().style(Style::new().bg(color_scheme::BG_ELEVATED)),
                             );
                             textarea.move_cursor(tui_textarea::CursorMove::Jump(
-                                origin.y - area.y,
-                                origin.x - area.x,
+                                (origin.y - area.y) as u16,
+                                (origin.x - area.x) as u16,
                             ));
-                            self.current_operation = Some(Operation::EditText { textarea });
+                            self.checkpoint();
+                            self.selected_elements.clear();
+                            self.selected_elements.insert(id);
+                            self.current_operation = Some(Operation::EditText {
+                                textarea: Box::new(textarea),
+                            });
                             Ok(Some(Action::EditText))
                         } else if area.width > 1 && area.height >= 1 {
-                            self.canvas.elements.push_back(Element::Text {
+                            self.checkpoint();
+                            let id = self.canvas.push(ElementKind::Text {
                                 area,
                                 content: "".into(),
+                                alignment: Default::default(),
                             });
-                            self.selected_elements
-                                .insert(self.canvas.elements.len() - 1);
+                            self.selected_elements.insert(id);
                             let mut textarea = TextArea::default();
                             textarea.set_block(
                                 Block::new().style(Style::new().bg(color_scheme::BG_ELEVATED)),
                             );
-                            self.current_operation = Some(Operation::EditText { textarea });
+                            self.current_operation = Some(Operation::EditText {
+                                textarea: Box::new(textarea),
+                            });
                             Ok(Some(Action::EditText))
                         } else {
                             self.reset_tool();
@@ -404,11 +1081,32 @@ impl Component for Home {
                         Ok(None)
                     }
                 }
+                Tool::Paint | Tool::Eraser => {
+                    // Layers that were erased completely aren't worth keeping
+                    let empty = self
+                        .canvas
+                        .elements
+                        .iter()
+                        .filter(|el| matches!(&el.kind, ElementKind::Paint { pixels } if pixels.is_empty()))
+                        .map(|el| el.id)
+                        .collect::<HashSet<_>>();
+                    self.canvas.remove(&empty);
+                    self.selected_elements.retain(|id| !empty.contains(id));
+                    self.finish_stroke();
+                    self.current_operation = None;
+                    Ok(Some(Action::RenderBuffer))
+                }
                 Tool::Cursor => {
-                    if let Some(op) = &self.current_operation {
-                        for i in &self.selected_elements {
-                            if let Some(element) = op.apply_transform(&self.canvas.elements[*i]) {
-                                self.canvas.elements[*i] = element;
+                    if let Some(op) = self.current_operation.take() {
+                        if !matches!(op, Operation::Selection { .. }) {
+                            self.checkpoint();
+                        }
+                        for element in self.canvas.elements.iter_mut() {
+                            if !self.selected_elements.contains(&element.id) {
+                                continue;
+                            }
+                            if let Some(transformed) = op.apply_transform(element) {
+                                *element = transformed;
                             }
                         }
                         self.current_operation = None;
@@ -430,8 +1128,7 @@ impl Component for Home {
     fn update(&mut self, action: Action) -> Result<Option<Action>> {
         match action {
             Action::RenderBuffer => {
-                self.canvas
-                    .render(&self.selected_elements, &self.current_operation);
+                self.render_canvas();
                 Ok(None)
             }
             Action::SwitchTool(tool) => {
@@ -439,18 +1136,25 @@ impl Component for Home {
                     if self.current_operation.is_some() {
                         self.current_operation = None;
                     }
+                    self.finish_stroke();
 
                     self.current_tool = tool;
                 }
                 Ok(None)
             }
+            Action::SetBrush(brush) => {
+                self.brush = Some(brush);
+                self.update_tool(Tool::Paint);
+                Ok(None)
+            }
             Action::EditText => Ok(Some(Action::RenderBuffer)),
             Action::CommitText => {
-                if let Some(Element::Text { content, .. }) = self
+                if let Some(ElementKind::Text { content, .. }) = self
                     .selected_elements
                     .iter()
                     .next()
-                    .and_then(|i| self.canvas.elements.get_mut(*i))
+                    .and_then(|id| self.canvas.get_mut(*id))
+                    .map(|el| &mut el.kind)
                 {
                     *content =
                         if let Some(Operation::EditText { textarea }) = &self.current_operation {
@@ -462,57 +1166,344 @@ impl Component for Home {
                 self.current_operation = None;
                 Ok(Some(Action::RenderBuffer))
             }
+            Action::FinishPolyline => {
+                if let Some(Operation::DrawPolyline { points, .. }) = &self.current_operation {
+                    let points = points.clone();
+                    self.current_operation = None;
+                    if points.len() >= 2 {
+                        self.checkpoint();
+                        let id = self
+                            .canvas
+                            .push(ElementKind::Polyline(Polyline::new(points)));
+                        self.reset_tool();
+                        self.selected_elements.insert(id);
+                    }
+                }
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::SetTitle(title) => {
+                if self.canvas.iter_selected(&self.selected_elements).any(
+                    |el| matches!(&el.kind, ElementKind::Box { style, .. } if style.title != title),
+                ) {
+                    self.checkpoint();
+                }
+                for id in self.selected_elements.iter() {
+                    if let Some(ElementKind::Box { style, .. }) =
+                        self.canvas.get_mut(*id).map(|el| &mut el.kind)
+                    {
+                        style.title = title.clone();
+                    }
+                }
+                Ok(Some(Action::RenderBuffer))
+            }
             Action::SelectAll => {
                 self.update_tool(Tool::Cursor);
-                self.selected_elements =
-                    (0..self.canvas.elements.len()).collect::<HashSet<usize>>();
+                self.selected_elements = self.canvas.elements.iter().map(|el| el.id).collect();
                 Ok(Some(Action::RenderBuffer))
             }
             Action::SelectNone => {
+                if let Some(Operation::DrawPolyline { .. }) = self.current_operation {
+                    self.current_operation = None;
+                }
                 self.selected_elements.clear();
                 Ok(Some(Action::RenderBuffer))
             }
             Action::Delete => {
-                self.canvas.elements = self
+                if self
+                    .canvas
+                    .iter_selected(&self.selected_elements)
+                    .next()
+                    .is_some()
+                {
+                    self.checkpoint();
+                }
+                self.canvas.remove(&self.selected_elements);
+                Ok(Some(Action::SelectNone))
+            }
+            Action::Yank => {
+                if self.selected_elements.is_empty() {
+                    return Ok(None);
+                }
+                self.clipboard = self.copy_selection();
+                Ok(Some(Action::CopyToClipboard(buffer_to_string(
+                    &self.canvas.render_selection(&self.selected_elements),
+                ))))
+            }
+            Action::Paste => {
+                let Some(area) = self
+                    .clipboard
+                    .iter()
+                    .map(Element::area)
+                    .reduce(|a, b| a.union(b))
+                else {
+                    return Ok(None);
+                };
+                let target = self
+                    .mouse_position
+                    .unwrap_or(Point::new(area.x + 2, area.y + 1));
+                self.insert_elements(self.clipboard.clone(), target.offset_from(area.as_point()));
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Duplicate => {
+                if self.selected_elements.is_empty() {
+                    return Ok(None);
+                }
+                self.insert_elements(self.copy_selection(), Offset { x: 2, y: 1 });
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Raise | Action::Lower | Action::RaiseToTop | Action::LowerToBottom => {
+                if self.selected_elements.is_empty() {
+                    return Ok(None);
+                }
+                self.checkpoint();
+                match action {
+                    Action::Raise => self.canvas.raise(&self.selected_elements),
+                    Action::Lower => self.canvas.lower(&self.selected_elements),
+                    Action::RaiseToTop => self.canvas.raise_to_top(&self.selected_elements),
+                    _ => self.canvas.lower_to_bottom(&self.selected_elements),
+                };
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Group => {
+                if self.selected_elements.is_empty() {
+                    return Ok(None);
+                }
+                self.checkpoint();
+                if let Some(group) = self.canvas.group(&self.selected_elements) {
+                    self.selected_elements = HashSet::from([group]);
+                    self.layer_cursor = Some(group);
+                }
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Align(align) => {
+                if self.selected_elements.len() < 2 {
+                    return Ok(None);
+                }
+                self.checkpoint();
+                self.canvas.align(&self.selected_elements, align);
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Distribute(axis) => {
+                if self.selected_elements.len() < 3 {
+                    return Ok(None);
+                }
+                self.checkpoint();
+                self.canvas.distribute(&self.selected_elements, axis);
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Ungroup => {
+                if !self
+                    .canvas
+                    .iter_selected(&self.selected_elements)
+                    .any(|el| matches!(el.kind, ElementKind::Group { .. }))
+                {
+                    return Ok(None);
+                }
+                self.checkpoint();
+                let released = self.canvas.ungroup(&self.selected_elements);
+                self.selected_elements
+                    .retain(|id| self.canvas.get(*id).is_some());
+                self.selected_elements.extend(released);
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::ToggleGroup => {
+                if let Some(cursor) = self.layer_cursor {
+                    if !self.expanded_groups.remove(&cursor) {
+                        self.expanded_groups.insert(cursor);
+                    }
+                }
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::FocusLayers => {
+                self.layers_focused = true;
+                self.style_focused = false;
+                self.layer_cursor = self
                     .canvas
                     .elements
                     .iter()
-                    .enumerate()
-                    .filter_map(|(i, el)| {
-                        self.selected_elements
-                            .contains(&i)
-                            .not()
-                            .then_some(el.clone())
+                    .rev()
+                    .find(|el| {
+                        self.selected_elements.is_empty() || self.selected_elements.contains(&el.id)
                     })
-                    .collect::<Vec<_>>()
-                    .into();
-                Ok(Some(Action::SelectNone))
+                    .map(|el| el.id);
+                Ok(None)
+            }
+            Action::FocusCanvas => {
+                self.layers_focused = false;
+                self.style_focused = false;
+                Ok(None)
+            }
+            Action::FocusStyle => {
+                self.style_focused = true;
+                self.layers_focused = false;
+                Ok(None)
+            }
+            Action::StyleCursorUp => {
+                self.move_style_cursor(1);
+                Ok(None)
+            }
+            Action::StyleCursorDown => {
+                self.move_style_cursor(-1);
+                Ok(None)
+            }
+            Action::PropertyIncrease | Action::PropertyDecrease => {
+                let by = if action == Action::PropertyIncrease {
+                    1
+                } else {
+                    -1
+                };
+                Ok(self.current_property().and_then(|property| {
+                    self.change_selection(|kind| {
+                        property.step(kind, by);
+                        Ok(())
+                    })
+                }))
+            }
+            Action::ActivateProperty => Ok(self.activate_property()),
+            Action::CommitProperty => {
+                let input = self.property_input.take();
+                Ok(input
+                    .zip(self.current_property())
+                    .and_then(|(input, property)| {
+                        let value = input.lines().join("");
+                        self.change_selection(|kind| property.set(kind, &value))
+                    }))
+            }
+            Action::CancelProperty => {
+                self.property_input = None;
+                Ok(None)
+            }
+            Action::LayerCursorUp | Action::LayerSelectUp => {
+                self.move_layer_cursor(1, action == Action::LayerSelectUp);
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::LayerCursorDown | Action::LayerSelectDown => {
+                self.move_layer_cursor(-1, action == Action::LayerSelectDown);
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::ToggleLayer => {
+                if let Some(cursor) = self.layer_cursor {
+                    if !self.selected_elements.remove(&cursor) {
+                        self.selected_elements.insert(cursor);
+                    }
+                }
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Undo => {
+                if let Some(snapshot) = self.history.undo(self.snapshot()) {
+                    self.restore(snapshot);
+                }
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Redo => {
+                if let Some(snapshot) = self.history.redo(self.snapshot()) {
+                    self.restore(snapshot);
+                }
+                Ok(Some(Action::RenderBuffer))
             }
             Action::OpenCommandPalette => {
-                self.selected_elements.clear();
+                self.layers_focused = false;
                 Ok(None)
             }
             Action::ScrollUp => {
-                self.scroll_offset.y = self.scroll_offset.y.saturating_sub(SCROLL_STEP);
+                self.viewport.y -= SCROLL_STEP;
                 Ok(Some(Action::RenderBuffer))
             }
             Action::ScrollDown => {
-                self.scroll_offset.y += SCROLL_STEP;
+                self.viewport.y += SCROLL_STEP;
                 Ok(Some(Action::RenderBuffer))
             }
             Action::ScrollLeft => {
-                self.scroll_offset.x = self.scroll_offset.x.saturating_sub(SCROLL_STEP * 2);
+                self.viewport.x -= SCROLL_STEP * 2;
                 Ok(Some(Action::RenderBuffer))
             }
             Action::ScrollRight => {
-                self.scroll_offset.x += SCROLL_STEP * 2;
+                self.viewport.x += SCROLL_STEP * 2;
                 Ok(Some(Action::RenderBuffer))
             }
-            Action::Export(path) => {
-                let mut file = File::create(PathBuf::from(path))?;
-                file.write_all(&self.canvas.to_string())?;
+            Action::ToggleRulers => {
+                self.rulers = !self.rulers;
+                Ok(None)
+            }
+            Action::SetGrid(size) => {
+                self.grid = size;
+                Ok(None)
+            }
+            Action::New => {
+                self.canvas = DrawingCanvas::default();
+                self.history = History::default();
+                self.current_operation = None;
+                self.viewport = Point::ORIGIN;
+                self.reset_tool();
+                Ok(Some(Action::RenderBuffer))
+            }
+            Action::Open(path) => match Document::open(&path) {
+                Ok(document) => {
+                    self.canvas = DrawingCanvas::new(document.elements);
+                    self.canvas.guides = document.guides;
+                    self.history = History::default();
+                    self.current_operation = None;
+                    self.viewport = Point::ORIGIN;
+                    self.reset_tool();
+                    Ok(Some(Action::RenderBuffer))
+                }
+                Err(err) => Ok(Some(Action::Error(format!(
+                    "Failed to open {}: {:?}",
+                    path, err
+                )))),
+            },
+            Action::Save(path) => {
+                if let Err(err) =
+                    Document::new(self.canvas.elements.clone(), self.canvas.guides.clone())
+                        .save(&path)
+                {
+                    return Ok(Some(Action::Error(format!(
+                        "Failed to save {}: {:?}",
+                        path, err
+                    ))));
+                }
                 Ok(None)
             }
+            Action::Export(path) => {
+                let wrap = self.config.config.text_export.wrap;
+                Ok(self.export_text(&path, &buffer_to_string(&self.render_unselected()), wrap))
+            }
+            Action::ExportWrapped(wrap, path) => {
+                Ok(self.export_text(&path, &buffer_to_string(&self.render_unselected()), wrap))
+            }
+            Action::ExportAscii(path) => {
+                let wrap = self.config.config.text_export.wrap;
+                let text = export::buffer_to_ascii(&self.render_unselected());
+                Ok(self.export_text(&path, &text, wrap))
+            }
+            Action::ExportSelected(path) => {
+                if self.selected_elements.is_empty() {
+                    return Ok(Some(Action::Error("Nothing is selected".into())));
+                }
+                let text = buffer_to_string(&self.canvas.render_selection(&self.selected_elements));
+                Ok(self.export_text(&path, &text, self.config.config.text_export.wrap))
+            }
+            Action::ExportSvg(ref path)
+            | Action::ExportHtml(ref path)
+            | Action::ExportAnsi(ref path) => {
+                let buffer = self.render_unselected();
+                let contents = match action {
+                    Action::ExportSvg(_) => export::buffer_to_svg(&buffer, &self.config.config.svg),
+                    Action::ExportHtml(_) => export::buffer_to_html(&buffer),
+                    _ => export::buffer_to_ansi(&buffer),
+                };
+                Ok(write_export(path, &contents))
+            }
+            Action::Import(path) => match read_to_string(&path) {
+                Ok(input) => {
+                    self.insert_kinds(import::parse_plaintext(&input));
+                    Ok(Some(Action::RenderBuffer))
+                }
+                Err(err) => Ok(Some(Action::Error(format!(
+                    "Failed to import {}: {:?}",
+                    path, err
+                )))),
+            },
             _ => Ok(None),
         }
     }
@@ -528,6 +1519,29 @@ impl Component for Home {
         let [layers_area, canvas_area, style_area] =
             Layout::horizontal([Length(LIST_WIDTH), Fill(1), Length(STYLE_WIDTH)]).areas(area);
 
+        let (top_ruler, left_ruler, canvas_area) = if self.rulers {
+            let [top_ruler, rest] = Layout::vertical([Length(1), Fill(1)]).areas(canvas_area);
+            let [left_ruler, canvas_area] =
+                Layout::horizontal([Length(RULER_WIDTH), Fill(1)]).areas(rest);
+            let top_ruler = Rect {
+                x: canvas_area.x,
+                width: canvas_area.width,
+                ..top_ruler
+            };
+            (top_ruler, left_ruler, canvas_area)
+        } else {
+            (Rect::default(), Rect::default(), canvas_area)
+        };
+        if canvas_area != self.canvas_area {
+            self.canvas_area = canvas_area;
+            self.render_canvas();
+        }
+        self.top_ruler = top_ruler;
+        self.left_ruler = left_ruler;
+        if self.rulers {
+            self.draw_rulers(frame, top_ruler, left_ruler);
+        }
+
         frame.render_widget(
             Paragraph::new(Text::from(
                 (0..canvas_area.height)
@@ -556,112 +1570,105 @@ impl Component for Home {
 
         // Content
 
-        let xs = 0..canvas_area.width;
-        let ys = 0..canvas_area.height;
+        // Drawing to the frame with this origin puts the viewport into the
+        // top left corner of the canvas area
+        let screen_origin = Point::new(
+            self.viewport.x - canvas_area.x as i32,
+            self.viewport.y - canvas_area.y as i32,
+        );
+        let to_screen = |point: Point| {
+            point
+                .to_position(screen_origin)
+                .filter(|position| canvas_area.contains(*position))
+        };
 
-        for (x, y) in xs.flat_map(|x| ys.clone().map(move |y| (x, y))) {
-            if let Some(cell) = self
-                .canvas
-                .buffer
-                .cell((x + self.scroll_offset.x, y + self.scroll_offset.y))
+        for position in canvas_area.positions() {
+            let point = Point::new(
+                position.x as i32 + screen_origin.x,
+                position.y as i32 + screen_origin.y,
+            );
+            if let Some(cell) = point
+                .to_position(self.canvas.origin)
+                .and_then(|position| self.canvas.buffer.cell(position))
             {
-                if let Some(frame_cell) = frame.buffer_mut().cell_mut((x + canvas_area.x, y)) {
+                if let Some(frame_cell) = frame.buffer_mut().cell_mut(position) {
                     frame_cell.set_symbol(cell.symbol());
                     frame_cell.set_fg(cell.fg);
                 }
             }
         }
 
-        // Resize Handles
+        // Guides
 
-        if self.selected_elements.len() == 1 {
-            if let Some(el) = self
-                .selected_elements
+        for position in canvas_area.positions() {
+            let point = Point::new(
+                position.x as i32 + screen_origin.x,
+                position.y as i32 + screen_origin.y,
+            );
+            let horizontal = self
+                .canvas
+                .guides
                 .iter()
-                .next()
-                .and_then(|i| self.canvas.elements.get(*i))
+                .any(|guide| matches!(*guide, Guide::Horizontal { y } if y == point.y));
+            let vertical = self
+                .canvas
+                .guides
+                .iter()
+                .any(|guide| matches!(*guide, Guide::Vertical { x } if x == point.x));
+            let symbol = match (horizontal, vertical) {
+                (true, true) => "┼",
+                (true, false) => "┄",
+                (false, true) => "┆",
+                (false, false) => continue,
+            };
+            // Guides stay behind the drawing
+            if let Some(cell) = frame
+                .buffer_mut()
+                .cell_mut(position)
+                .filter(|cell| cell.symbol() == " ")
             {
-                let trans = self
-                    .current_operation
-                    .as_ref()
-                    .and_then(|op| op.apply_transform(el));
-
-                let el = trans.as_ref().unwrap_or(el);
-
-                if let Element::Line(line) = el {
-                    use crate::drawing::LineDirection::*;
-                    let style = Style::new().fg(color_scheme::FG_SELECTION);
-
-                    match line.direction {
-                        Right => [
-                            (line.from.x as i16 - 1, line.from.y as i16),
-                            (line.to.x as i16 + 1, line.to.y as i16),
-                        ],
-                        DownRight => [
-                            (line.from.x as i16 - 2, line.from.y as i16 - 1),
-                            (line.to.x as i16 + 2, line.to.y as i16 + 1),
-                        ],
-                        Down => [
-                            (line.from.x as i16, line.from.y as i16 - 1),
-                            (line.to.x as i16, line.to.y as i16 + 1),
-                        ],
-                        DownLeft => [
-                            (line.from.x as i16 + 2, line.from.y as i16 - 1),
-                            (line.to.x as i16 - 2, line.to.y as i16 + 1),
-                        ],
-                        Left => [
-                            (line.to.x as i16 - 1, line.to.y as i16),
-                            (line.from.x as i16 + 1, line.from.y as i16),
-                        ],
-                        UpLeft => [
-                            (line.to.x as i16 - 2, line.to.y as i16 - 1),
-                            (line.from.x as i16 + 2, line.from.y as i16 + 1),
-                        ],
-                        Up => [
-                            (line.to.x as i16, line.to.y as i16 - 1),
-                            (line.from.x as i16, line.from.y as i16 + 1),
-                        ],
-                        UpRight => [
-                            (line.to.x as i16 + 2, line.to.y as i16 - 1),
-                            (line.from.x as i16 - 2, line.from.y as i16 + 1),
-                        ],
-                    }
-                    .into_iter()
-                    .map(|(x, y)| {
-                        (
-                            area.x.checked_add_signed(
-                                x - self.scroll_offset.x as i16 + canvas_area.x as i16,
-                            ),
-                            area.y.checked_add_signed(
-                                y - self.scroll_offset.y as i16 + canvas_area.y as i16,
-                            ),
-                        )
-                    })
-                    .filter_map(|(x, y)| x.zip(y).map(Position::from))
-                    .filter(|pos| canvas_area.contains(*pos))
-                    .map(|Position { x, y }| (Rect::new(x, y, 1, 1), Span::styled("■", style)))
-                    .for_each(|(rect, s)| frame.render_widget(s, rect));
-                } else {
-                    draw_resize_handles(frame, &canvas_area, &el.area(), &self.scroll_offset);
-                }
+                cell.set_symbol(symbol).set_fg(color_scheme::FG_GUIDE);
+            }
+        }
+
+        // Resize Handles
+
+        if let Some(el) = self.single_selection() {
+            let trans = self
+                .current_operation
+                .as_ref()
+                .and_then(|op| op.apply_transform(el));
+
+            let el = trans.as_ref().unwrap_or(el);
+
+            let style = Style::new().fg(color_scheme::FG_SELECTION);
+            let handles = match &el.kind {
+                ElementKind::Line(line) => line_handles(line).to_vec(),
+                ElementKind::Polyline(polyline) => polyline.points.clone(),
+                _ => vec![],
+            };
+            for position in handles.into_iter().filter_map(to_screen) {
+                frame.render_widget(
+                    Span::styled("■", style),
+                    Rect::new(position.x, position.y, 1, 1),
+                );
             }
         }
 
+        if let Some(bounds) = self.resize_bounds() {
+            let bounds = self
+                .current_operation
+                .as_ref()
+                .map_or(bounds, |op| op.transform_area(&bounds));
+            draw_resize_handles(frame, &bounds, to_screen);
+        }
+
         // Selection
 
         match &self.current_operation {
             Some(Operation::Selection { origin, second }) => {
-                let sel_area = Rect {
-                    x: origin.x.min(second.x) - self.scroll_offset.x,
-                    y: origin.y.min(second.y) - self.scroll_offset.y,
-                    width: origin.x.abs_diff(second.x) + 1,
-                    height: origin.y.abs_diff(second.y) + 1,
-                }
-                .offset(Offset {
-                    x: canvas_area.x as i32,
-                    y: canvas_area.y as i32,
-                })
-                .intersection(canvas_area);
+                let area = Area::between(*origin, *second);
+                let sel_area = area.to_rect(screen_origin).intersection(canvas_area);
 
                 match self.current_tool {
                     Tool::Cursor => frame.render_widget(
@@ -672,6 +1679,16 @@ impl Component for Home {
                         Block::bordered().style(Style::new().fg(color_scheme::FG_BASE)),
                         sel_area,
                     ),
+                    Tool::Ellipse => ElementKind::Ellipse { area }.render_to(
+                        frame.buffer_mut(),
+                        screen_origin,
+                        false,
+                    ),
+                    Tool::Diamond => ElementKind::Diamond { area }.render_to(
+                        frame.buffer_mut(),
+                        screen_origin,
+                        false,
+                    ),
                     Tool::Text => {
                         frame.render_widget(Clear, sel_area);
                         frame.render_widget(
@@ -679,39 +1696,32 @@ impl Component for Home {
                             sel_area,
                         )
                     }
+                    Tool::Paint | Tool::Eraser | Tool::Polyline => (),
                     Tool::Line => {
-                        StraightLine::new(
-                            Position::new(
-                                canvas_area.x + origin.x - self.scroll_offset.x,
-                                canvas_area.y + origin.y - self.scroll_offset.y,
-                            ),
-                            Position::new(
-                                canvas_area.x + second.x - self.scroll_offset.x,
-                                canvas_area.y + second.y - self.scroll_offset.y,
-                            ),
-                        )
-                        .inspect(|l| {
-                            l.render_to(frame.buffer_mut(), Style::new().fg(color_scheme::FG_BASE))
+                        StraightLine::new(*origin, *second).inspect(|l| {
+                            l.render_to(
+                                frame.buffer_mut(),
+                                screen_origin,
+                                Style::new().fg(color_scheme::FG_BASE),
+                            )
                         });
                     }
                 };
             }
+            Some(Operation::DrawPolyline { points, cursor }) => {
+                let mut points = points.clone();
+                points.push(*cursor);
+                ElementKind::Polyline(Polyline::new(points)).render_to(
+                    frame.buffer_mut(),
+                    screen_origin,
+                    false,
+                );
+            }
             Some(Operation::EditText { textarea }) => {
-                if let Some(el) = self.selected_elements.iter().next() {
-                    frame.render_widget(
-                        Clear,
-                        self.canvas.elements[*el].area().offset(Offset {
-                            x: (canvas_area.x as i32 - self.scroll_offset.x as i32),
-                            y: (canvas_area.y as i32 - self.scroll_offset.y as i32),
-                        }),
-                    );
-                    frame.render_widget(
-                        textarea,
-                        self.canvas.elements[*el].area().offset(Offset {
-                            x: (canvas_area.x as i32 - self.scroll_offset.x as i32),
-                            y: (canvas_area.y as i32 - self.scroll_offset.y as i32),
-                        }),
-                    );
+                if let Some(el) = self.single_selection() {
+                    let area = el.area().to_rect(screen_origin).intersection(canvas_area);
+                    frame.render_widget(Clear, area);
+                    frame.render_widget(textarea.as_ref(), area);
                 }
             }
             _ => (),
@@ -721,8 +1731,26 @@ impl Component for Home {
 
         let [_, toolbox_area] = Layout::vertical([Fill(1), Length(3)]).areas(canvas_area);
 
+        let tools = [
+            "[v] Cursor".to_string(),
+            "[b] Box".to_string(),
+            "[e] Ellipse".to_string(),
+            "[r] Diamond".to_string(),
+            "[t] Text".to_string(),
+            "[l] Line".to_string(),
+            "[L] Polyline".to_string(),
+            format!("[f] Paint {}", self.brush.unwrap_or(DEFAULT_BRUSH)),
+            "[x] Eraser".to_string(),
+        ];
+        // Every tab is padded by a space on each side and followed by a divider
+        let toolbox_width = tools
+            .iter()
+            .map(|tool| Line::from(tool.as_str()).width() as u16 + 3)
+            .sum::<u16>()
+            + 1;
+
         frame.render_widget(
-            Tabs::new(vec!["[v] Cursor", "[b] Box", "[t] Text", "[l] Line"])
+            Tabs::new(tools.clone())
                 .style(Style::new().bg(color_scheme::BG_BASE))
                 .block(
                     Block::bordered()
@@ -737,10 +1765,15 @@ impl Component for Home {
                 .select(match self.current_tool {
                     Tool::Cursor => 0,
                     Tool::Box => 1,
-                    Tool::Text => 2,
-                    Tool::Line => 3,
+                    Tool::Ellipse => 2,
+                    Tool::Diamond => 3,
+                    Tool::Text => 4,
+                    Tool::Line => 5,
+                    Tool::Polyline => 6,
+                    Tool::Paint => 7,
+                    Tool::Eraser => 8,
                 }),
-            center_horizontal(toolbox_area, 46),
+            center_horizontal(toolbox_area, toolbox_width),
         );
 
         // Scrollbars
@@ -749,14 +1782,20 @@ impl Component for Home {
             .bg(color_scheme::BG_BASE)
             .fg(color_scheme::FG_MUTED);
 
-        let mut scrollbar_state = ScrollbarState::new(
-            self.canvas
-                .buffer
-                .area
-                .height
-                .saturating_sub(canvas_area.height) as usize,
-        )
-        .position(self.scroll_offset.y as usize);
+        // The scrollbars span the drawing and whatever is in view, so they
+        // can go past either end of the drawing
+        let drawing = self.canvas.area;
+        let view = Area::new(
+            self.viewport.x,
+            self.viewport.y,
+            canvas_area.width,
+            canvas_area.height,
+        );
+        let extent = drawing.union(view);
+
+        let mut scrollbar_state =
+            ScrollbarState::new(extent.height.saturating_sub(canvas_area.height) as usize)
+                .position((view.y - extent.y) as usize);
 
         frame.render_stateful_widget(
             Scrollbar::new(ScrollbarOrientation::VerticalRight)
@@ -771,14 +1810,9 @@ impl Component for Home {
             &mut scrollbar_state,
         );
 
-        let mut scrollbar_state = ScrollbarState::new(
-            self.canvas
-                .buffer
-                .area
-                .width
-                .saturating_sub(canvas_area.width) as usize,
-        )
-        .position(self.scroll_offset.x as usize);
+        let mut scrollbar_state =
+            ScrollbarState::new(extent.width.saturating_sub(canvas_area.width) as usize)
+                .position((view.x - extent.x) as usize);
 
         frame.render_stateful_widget(
             Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
@@ -796,65 +1830,179 @@ impl Component for Home {
 
         // Style Editor
 
-        let [position_area, border_area, shadow_area] =
-            Layout::vertical([Length(6), Length(8), Length(6)]).areas(style_area);
+        self.style_area = style_area;
+        let kinds = self.selected_kinds();
+        let current_property = self.current_property().filter(|_| self.style_focused);
 
-        if self.selected_elements.len() == 1 {
-            frame.render_widget(
-                Block::bordered()
-                    .title("Position")
-                    .style(Style::new().fg(color_scheme::FG_BASE))
-                    .border_style(Style::default().fg(color_scheme::FG_MUTED)),
-                position_area,
-            );
-            frame.render_widget(
-                Block::bordered()
-                    .title("Border")
-                    .style(Style::new().fg(color_scheme::FG_BASE))
-                    .border_style(Style::default().fg(color_scheme::FG_MUTED)),
-                border_area,
-            );
+        for (area, title, properties) in self.style_sections() {
             frame.render_widget(
                 Block::bordered()
-                    .title("Shadow")
+                    .title(title)
                     .style(Style::new().fg(color_scheme::FG_BASE))
                     .border_style(Style::default().fg(color_scheme::FG_MUTED)),
-                shadow_area,
+                area,
             );
+
+            let inner = area.inner(Margin::new(1, 1));
+            for (property, row) in properties.into_iter().zip(inner.rows()) {
+                let highlight = if current_property == Some(property) {
+                    Style::new().bg(color_scheme::BG_ELEVATED)
+                } else {
+                    Style::new()
+                };
+                let read_only = kinds.iter().all(|kind| property.is_read_only(kind));
+                let [name_area, value_area] = Layout::horizontal([Length(7), Fill(1)]).areas(row);
+
+                frame.render_widget(
+                    Span::styled(property.name(), Style::new().fg(color_scheme::FG_SECONDARY))
+                        .patch_style(highlight),
+                    name_area,
+                );
+                frame.render_widget(Block::new().style(highlight), value_area);
+
+                match &self.property_input {
+                    Some(textarea) if current_property == Some(property) => {
+                        frame.render_widget(textarea, value_area)
+                    }
+                    _ => frame.render_widget(
+                        match property::common_value(property, &kinds) {
+                            Some(value) if value.is_empty() => {
+                                Span::styled("-", Style::new().fg(color_scheme::FG_MUTED))
+                            }
+                            Some(value) if read_only => {
+                                Span::styled(value, Style::new().fg(color_scheme::FG_MUTED))
+                            }
+                            Some(value) => Span::raw(value),
+                            None => Span::styled("mixed", Style::new().fg(color_scheme::FG_MUTED)),
+                        }
+                        .patch_style(highlight),
+                        value_area,
+                    ),
+                }
+            }
         }
 
-        frame.render_widget(
+        let rows = layer_rows(&self.canvas.elements, &self.expanded_groups);
+
+        self.layers_state.select(
+            self.layer_cursor
+                .filter(|_| self.layers_focused)
+                .and_then(|cursor| rows.iter().position(|row| row.element.id == cursor)),
+        );
+
+        frame.render_stateful_widget(
             List::new(
-                self.canvas
-                    .elements
-                    .iter()
-                    .map(|x| format!(" {}", x.name()))
-                    .enumerate()
-                    .map(|(i, x)| {
-                        if self.selected_elements.contains(&i) {
-                            x.fg(color_scheme::FG_SELECTION)
+                rows.iter()
+                    .map(|row| {
+                        let arrow = match row.element.kind {
+                            ElementKind::Group { .. }
+                                if self.expanded_groups.contains(&row.element.id) =>
+                            {
+                                "▾ "
+                            }
+                            ElementKind::Group { .. } => "▸ ",
+                            _ => "",
+                        };
+                        let name =
+                            format!(" {}{}{}", "  ".repeat(row.depth), arrow, row.element.name());
+                        if self.selected_elements.contains(&row.layer) {
+                            name.fg(color_scheme::FG_SELECTION)
                         } else {
-                            Span::raw(x)
+                            Span::raw(name)
                         }
                     })
-                    .rev()
                     .collect::<Vec<_>>(),
             )
+            .highlight_style(Style::new().bg(color_scheme::BG_ELEVATED))
             .block(
                 Block::new()
                     .title(Span::styled(
                         " Layers",
-                        Style::new().fg(color_scheme::FG_SECONDARY),
+                        Style::new().fg(if self.layers_focused {
+                            color_scheme::FG_BASE
+                        } else {
+                            color_scheme::FG_SECONDARY
+                        }),
                     ))
                     .style(Style::default().fg(color_scheme::FG_MUTED)),
             ),
             layers_area,
+            &mut self.layers_state,
         );
 
         Ok(())
     }
 }
 
+impl Home {
+    /// Draws the rulers along the top and left edge of the canvas, labelled
+    /// with canvas coordinates. They're ticked at the lines of the grid, and
+    /// arrows point at the guides.
+    fn draw_rulers(&self, frame: &mut Frame, top: Rect, left: Rect) {
+        let style = Style::new()
+            .bg(color_scheme::BG_ELEVATED)
+            .fg(color_scheme::FG_MUTED);
+        let guide_style = style.fg(color_scheme::FG_GUIDE);
+        let is_tick = |value: i32| match self.grid {
+            0 | 1 => value.rem_euclid(5) == 0,
+            grid => value.rem_euclid(grid as i32) == 0,
+        };
+
+        frame.render_widget(Block::new().style(style), top);
+        frame.render_widget(Block::new().style(style), left);
+        let buffer = frame.buffer_mut();
+
+        for column in top.left()..top.right() {
+            let x = self.viewport.x + (column - top.x) as i32;
+            if x.rem_euclid(10) == 0 {
+                buffer.set_stringn(
+                    column,
+                    top.y,
+                    x.to_string(),
+                    (top.right() - column) as usize,
+                    style,
+                );
+            }
+        }
+        for column in top.left()..top.right() {
+            let x = self.viewport.x + (column - top.x) as i32;
+            let cell = &mut buffer[(column, top.y)];
+            if self.canvas.guides.contains(&Guide::Vertical { x }) {
+                cell.set_symbol("▼").set_style(guide_style);
+            } else if is_tick(x) && cell.symbol() == " " {
+                cell.set_symbol("·");
+            }
+        }
+
+        let label_width = (RULER_WIDTH - 1) as usize;
+        for row in left.top()..left.bottom() {
+            let y = self.viewport.y + (row - left.y) as i32;
+            if y.rem_euclid(5) == 0 {
+                buffer.set_stringn(
+                    left.x,
+                    row,
+                    format!("{y:>label_width$}"),
+                    label_width,
+                    style,
+                );
+            } else if is_tick(y) {
+                buffer.set_string(left.x + label_width as u16 - 1, row, "·", style);
+            }
+            if self.canvas.guides.contains(&Guide::Horizontal { y }) {
+                buffer.set_string(left.right() - 1, row, "▶", guide_style);
+            }
+        }
+    }
+}
+
+/// Writes exported `contents` to `path`, turning a failure into an error for
+/// the user rather than for the app.
+fn write_export(path: &str, contents: &str) -> Option<Action> {
+    fs::write(path, contents)
+        .err()
+        .map(|err| Action::Error(format!("Failed to export {}: {:?}", path, err)))
+}
+
 fn center_horizontal(area: Rect, width: u16) -> Rect {
     let [area] = Layout::horizontal([Constraint::Length(width)])
         .flex(Flex::Center)
@@ -862,27 +2010,22 @@ fn center_horizontal(area: Rect, width: u16) -> Rect {
     area
 }
 
-fn draw_resize_handles(frame: &mut Frame, canvas_area: &Rect, area: &Rect, offset: &Position) {
+fn draw_resize_handles(
+    frame: &mut Frame,
+    area: &Area,
+    to_screen: impl Fn(Point) -> Option<Position>,
+) {
     let style = Style::new().fg(color_scheme::FG_SELECTION);
+    let (left, top) = (area.left() - 1, area.top() - 1);
 
     [
-        (-1, -1, "▄"),
-        (area.width as i16, -1, "▄"),
-        (-1, area.height as i16, "▀"),
-        (area.width as i16, area.height as i16, "▀"),
+        (left, top, "▄"),
+        (area.right(), top, "▄"),
+        (left, area.bottom(), "▀"),
+        (area.right(), area.bottom(), "▀"),
     ]
     .into_iter()
-    .map(|(x, y, s)| {
-        (
-            area.x
-                .checked_add_signed(x - offset.x as i16 + canvas_area.x as i16),
-            area.y
-                .checked_add_signed(y - offset.y as i16 + canvas_area.y as i16),
-            s,
-        )
-    })
-    .filter_map(|(x, y, s)| x.zip(y).map(Position::from).zip(Some(s)))
-    .filter(|(pos, _)| canvas_area.contains(*pos))
+    .filter_map(|(x, y, s)| to_screen(Point::new(x, y)).zip(Some(s)))
     .map(|(Position { x, y }, s)| (Rect::new(x, y, 1, 1), Span::styled(s, style)))
     .for_each(|(rect, s)| frame.render_widget(s, rect));
 }
@@ -11,7 +11,11 @@ use ratatui::style::{Color, Modifier, Style};
 use serde::{de::Deserializer, Deserialize};
 use tracing::error;
 
-use crate::{action::Action, app::Mode};
+use crate::{
+    action::Action,
+    app::Mode,
+    drawing::export::{SvgOptions, TextOptions},
+};
 
 const CONFIG: &str = include_str!("../.config/config.json5");
 
@@ -21,6 +25,16 @@ pub struct AppConfig {
     pub data_dir: PathBuf,
     #[serde(default)]
     pub config_dir: PathBuf,
+    #[serde(default)]
+    pub osc52_clipboard: bool,
+    #[serde(default)]
+    pub grid_size: u16,
+    #[serde(default)]
+    pub rulers: bool,
+    #[serde(default)]
+    pub svg: SvgOptions,
+    #[serde(default)]
+    pub text_export: TextOptions,
 }
 
 #[derive(Clone, Debug, Default, Deserialize)]
@@ -1,71 +1,956 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use ratatui::{buffer::Buffer, layout::Rect};
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::{Offset, Rect},
+};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
 
-use super::{Element, Operation};
+use super::{
+    junction::draw_joined, Area, Element, ElementId, ElementKind, Guide, Operation, Point, Routes,
+};
+
+/// What to line the selected elements up by: one of the edges of the
+/// selection, or its center.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter, EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Display, EnumIter, EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
 
 #[derive(Default)]
 pub struct DrawingCanvas {
     pub elements: VecDeque<Element>,
+    pub guides: Vec<Guide>,
+    /// The part of the canvas in view, as last rendered.
     pub buffer: Buffer,
+    /// Where the top left cell of `buffer` is on the canvas.
+    pub origin: Point,
+    /// The bounding box of everything on the canvas, as last rendered.
+    pub area: Area,
+    routes: Routes,
+    next_id: u64,
 }
 
 impl DrawingCanvas {
-    pub fn render(&mut self, selected_elements: &HashSet<usize>, operation: &Option<Operation>) {
-        let areas = self.elements.iter().enumerate().map(|(i, el)| {
-            if let Some(el) = selected_elements
-                .get(&i)
-                .and_then(|_| operation.as_ref().and_then(|op| op.apply_transform(el)))
+    pub fn new(elements: VecDeque<Element>) -> Self {
+        fn next_id<'a>(elements: impl Iterator<Item = &'a Element>) -> u64 {
+            elements
+                .map(|el| (el.id.0 + 1).max(next_id(el.kind.children().iter())))
+                .max()
+                .unwrap_or_default()
+        }
+        let next_id = next_id(elements.iter());
+
+        let mut elements = elements;
+        let mut routes = Routes::default();
+        route_connectors(&mut elements, &mut routes);
+
+        Self {
+            elements,
+            guides: Vec::new(),
+            buffer: Buffer::default(),
+            origin: Point::ORIGIN,
+            area: Area::default(),
+            routes,
+            next_id,
+        }
+    }
+
+    /// Adds a new element on top of all others and returns its ID.
+    pub fn push(&mut self, kind: ElementKind) -> ElementId {
+        let id = self.allocate_id();
+        self.elements.push_back(Element::new(id, kind));
+        id
+    }
+
+    /// Adds copies of `elements` on top of all others, moved by `offset`, and
+    /// returns their IDs. The copies and everything nested in them get new
+    /// IDs, and connectors copied along with the elements they're bound to
+    /// are bound to the copies.
+    pub fn insert(&mut self, elements: Vec<Element>, offset: Offset) -> Vec<ElementId> {
+        let mut ids = HashMap::new();
+        let mut inserted = Vec::new();
+        for element in elements {
+            let mut element = Element::new(element.id, element.kind.offset(offset));
+            element.visit_mut(&mut |el| {
+                let id = self.allocate_id();
+                ids.insert(el.id, id);
+                el.id = id;
+            });
+            inserted.push(element.id);
+            self.elements.push_back(element);
+        }
+        for el in self.elements.iter_mut().rev().take(inserted.len()) {
+            el.visit_mut(&mut |el| {
+                if let ElementKind::Connector(connector) = &mut el.kind {
+                    connector.rebind(&ids);
+                }
+            });
+        }
+        inserted
+    }
+
+    fn allocate_id(&mut self) -> ElementId {
+        let id = ElementId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    /// Wraps the selected elements into a new group, which takes the place of
+    /// the topmost of them. Returns the ID of the group.
+    pub fn group(&mut self, selected_elements: &HashSet<ElementId>) -> Option<ElementId> {
+        let top = self
+            .elements
+            .iter()
+            .rposition(|el| selected_elements.contains(&el.id))?;
+        let position = self
+            .elements
+            .iter()
+            .take(top)
+            .filter(|el| !selected_elements.contains(&el.id))
+            .count();
+
+        let (children, mut rest): (Vec<_>, Vec<_>) = self
+            .elements
+            .drain(..)
+            .partition(|el| selected_elements.contains(&el.id));
+
+        let id = self.allocate_id();
+        rest.insert(position, Element::new(id, ElementKind::Group { children }));
+        self.elements = rest.into();
+
+        Some(id)
+    }
+
+    /// Replaces the selected groups by their children. Returns the IDs of the
+    /// released children.
+    pub fn ungroup(&mut self, selected_elements: &HashSet<ElementId>) -> HashSet<ElementId> {
+        let mut released = HashSet::new();
+        let mut ungrouped = HashSet::new();
+
+        self.elements = self
+            .elements
+            .drain(..)
+            .flat_map(|el| match el.kind {
+                ElementKind::Group { children } if selected_elements.contains(&el.id) => {
+                    released.extend(children.iter().map(|child| child.id));
+                    ungrouped.insert(el.id);
+                    children
+                }
+                kind => vec![Element::new(el.id, kind)],
+            })
+            .collect();
+
+        for el in self.elements.iter_mut() {
+            el.visit_mut(&mut |el| {
+                if let ElementKind::Connector(connector) = &mut el.kind {
+                    connector.detach(&ungrouped);
+                }
+            });
+        }
+
+        released
+    }
+
+    pub fn get(&self, id: ElementId) -> Option<&Element> {
+        self.elements.iter().find(|el| el.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: ElementId) -> Option<&mut Element> {
+        self.elements.iter_mut().find(|el| el.id == id)
+    }
+
+    pub fn index_of(&self, id: ElementId) -> Option<usize> {
+        self.elements.iter().position(|el| el.id == id)
+    }
+
+    /// Iterates over the given elements, back to front.
+    pub fn iter_selected<'a>(
+        &'a self,
+        selected_elements: &'a HashSet<ElementId>,
+    ) -> impl Iterator<Item = &'a Element> + 'a {
+        self.elements
+            .iter()
+            .filter(|el| selected_elements.contains(&el.id))
+    }
+
+    /// Removes the selected elements. Connectors bound to any of them, or to
+    /// anything nested in them, are detached.
+    pub fn remove(&mut self, selected_elements: &HashSet<ElementId>) {
+        let mut removed = HashSet::new();
+        for el in self.iter_selected(selected_elements) {
+            el.visit(&mut |el| {
+                removed.insert(el.id);
+            });
+        }
+
+        self.elements
+            .retain(|el| !selected_elements.contains(&el.id));
+
+        for el in self.elements.iter_mut() {
+            el.visit_mut(&mut |el| {
+                if let ElementKind::Connector(connector) = &mut el.kind {
+                    connector.detach(&removed);
+                }
+            });
+        }
+    }
+
+    /// Draws the part of the canvas within `viewport` into `buffer`.
+    pub fn render(
+        &mut self,
+        selected_elements: &HashSet<ElementId>,
+        operation: &Option<Operation>,
+        viewport: Area,
+    ) {
+        route_connectors(&mut self.elements, &mut self.routes);
+
+        // While an operation is in progress, connectors follow the elements it
+        // transforms, so they're routed again on a transformed copy
+        let preview = operation
+            .as_ref()
+            .filter(|_| !selected_elements.is_empty())
+            .map(|op| {
+                let mut elements = self.elements.clone();
+                for el in elements.iter_mut() {
+                    if let Some(transformed) = selected_elements
+                        .contains(&el.id)
+                        .then(|| op.apply_transform(el))
+                        .flatten()
+                    {
+                        *el = transformed;
+                    }
+                }
+                route_connectors(&mut elements, &mut self.routes);
+                elements
+            });
+        let elements = preview.as_ref().unwrap_or(&self.elements);
+
+        self.area = elements
+            .iter()
+            .map(Element::drawn_area)
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default();
+        self.origin = viewport.as_point();
+
+        if viewport.width != self.buffer.area().width
+            || viewport.height != self.buffer.area().height
+        {
+            self.buffer
+                .resize(Rect::new(0, 0, viewport.width, viewport.height));
+        }
+
+        self.buffer.content.fill(' '.into());
+
+        for element in elements
+            .iter()
+            .filter(|el| el.drawn_area().intersects(viewport))
+        {
+            draw_joined(
+                &mut self.buffer,
+                element.drawn_area().to_rect(self.origin),
+                |buffer| {
+                    draw_clipped(
+                        buffer,
+                        self.origin,
+                        element,
+                        selected_elements.contains(&element.id),
+                    )
+                },
+            );
+        }
+    }
+
+    /// Moves each selected element one layer up, past its unselected
+    /// neighbour.
+    pub fn raise(&mut self, selected_elements: &HashSet<ElementId>) {
+        for i in (0..self.elements.len().saturating_sub(1)).rev() {
+            if selected_elements.contains(&self.elements[i].id)
+                && !selected_elements.contains(&self.elements[i + 1].id)
             {
-                el.area()
-            } else {
-                el.area()
+                self.elements.swap(i, i + 1);
             }
-        });
+        }
+    }
+
+    /// Moves each selected element one layer down, past its unselected
+    /// neighbour.
+    pub fn lower(&mut self, selected_elements: &HashSet<ElementId>) {
+        for i in 1..self.elements.len() {
+            if selected_elements.contains(&self.elements[i].id)
+                && !selected_elements.contains(&self.elements[i - 1].id)
+            {
+                self.elements.swap(i, i - 1);
+            }
+        }
+    }
+
+    pub fn raise_to_top(&mut self, selected_elements: &HashSet<ElementId>) {
+        self.reorder(selected_elements, usize::MAX)
+    }
+
+    pub fn lower_to_bottom(&mut self, selected_elements: &HashSet<ElementId>) {
+        self.reorder(selected_elements, 0)
+    }
+
+    /// Moves the selected elements next to `target`: above it when moving up,
+    /// below it when moving down.
+    pub fn move_layers(&mut self, selected_elements: &HashSet<ElementId>, target: ElementId) {
+        let Some(target) = self
+            .index_of(target)
+            .filter(|_| !selected_elements.contains(&target))
+        else {
+            return;
+        };
+
+        let moving_up = self
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, el)| selected_elements.contains(&el.id))
+            .all(|(i, _)| i < target);
+
+        let position = self
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, el)| !selected_elements.contains(&el.id))
+            .filter(|(i, _)| if moving_up { *i <= target } else { *i < target })
+            .count();
 
-        let max_width = areas
-            .clone()
-            .map(|el| el.x + el.width)
+        self.reorder(selected_elements, position)
+    }
+
+    /// Takes the selected elements out of the stack and reinserts them, in
+    /// order, at `position` among the remaining ones.
+    fn reorder(&mut self, selected_elements: &HashSet<ElementId>, position: usize) {
+        let (selected, mut rest): (Vec<_>, Vec<_>) = self
+            .elements
+            .drain(..)
+            .partition(|el| selected_elements.contains(&el.id));
+
+        let position = position.min(rest.len());
+        rest.splice(position..position, selected);
+        self.elements = rest.into();
+    }
+
+    /// Lines the selected elements up along an edge or the center of the
+    /// selection.
+    pub fn align(&mut self, selected_elements: &HashSet<ElementId>, align: Align) {
+        let Some(bounds) = self
+            .iter_selected(selected_elements)
+            .filter(|el| is_aligned(el))
+            .map(Element::area)
+            .reduce(|a, b| a.union(b))
+        else {
+            return;
+        };
+        for el in self
+            .elements
+            .iter_mut()
+            .filter(|el| selected_elements.contains(&el.id) && is_aligned(el))
+        {
+            let area = el.area();
+            let centered =
+                |start: i32, size: u16, bounds_size: u16| start + (bounds_size - size) as i32 / 2;
+            let offset = match align {
+                Align::Left => Offset {
+                    x: bounds.left() - area.left(),
+                    y: 0,
+                },
+                Align::Center => Offset {
+                    x: centered(bounds.x, area.width, bounds.width) - area.x,
+                    y: 0,
+                },
+                Align::Right => Offset {
+                    x: bounds.right() - area.right(),
+                    y: 0,
+                },
+                Align::Top => Offset {
+                    x: 0,
+                    y: bounds.top() - area.top(),
+                },
+                Align::Middle => Offset {
+                    x: 0,
+                    y: centered(bounds.y, area.height, bounds.height) - area.y,
+                },
+                Align::Bottom => Offset {
+                    x: 0,
+                    y: bounds.bottom() - area.bottom(),
+                },
+            };
+            el.kind = el.kind.offset(offset);
+        }
+    }
+
+    /// Spaces the selected elements out evenly along `axis`, so the gaps
+    /// between them are equal. The outermost ones stay where they are.
+    pub fn distribute(&mut self, selected_elements: &HashSet<ElementId>, axis: Axis) {
+        let span = |area: Area| match axis {
+            Axis::Horizontal => (area.x, area.width as i32),
+            Axis::Vertical => (area.y, area.height as i32),
+        };
+
+        let mut spans = self
+            .iter_selected(selected_elements)
+            .filter(|el| is_aligned(el))
+            .map(|el| (span(el.area()), el.id))
+            .collect::<Vec<_>>();
+        if spans.len() < 3 {
+            return;
+        }
+        spans.sort();
+
+        let start = spans[0].0 .0;
+        let end = spans
+            .iter()
+            .map(|((position, size), _)| position + size)
             .max()
+            .unwrap_or(start);
+        let free = end - start - spans.iter().map(|((_, size), _)| size).sum::<i32>();
+        let gaps = spans.len() as i32 - 1;
+
+        let mut position = start;
+        for (i, ((current, size), id)) in spans.into_iter().enumerate() {
+            // Spread what doesn't divide evenly over the gaps, so the last
+            // element ends up where it was
+            let target = position + free * i as i32 / gaps;
+            if let Some(el) = self.get_mut(id) {
+                let by = target - current;
+                el.kind = el.kind.offset(match axis {
+                    Axis::Horizontal => Offset { x: by, y: 0 },
+                    Axis::Vertical => Offset { x: 0, y: by },
+                });
+            }
+            position += size;
+        }
+    }
+
+    /// Renders only the given elements, unselected, into a buffer cropped to
+    /// their combined bounding box.
+    pub fn render_selection(&self, selected_elements: &HashSet<ElementId>) -> Buffer {
+        let area = self
+            .iter_selected(selected_elements)
+            .map(Element::drawn_area)
+            .reduce(|a, b| a.union(b))
             .unwrap_or_default();
 
-        let max_height = areas.map(|el| el.y + el.height).max().unwrap_or_default();
+        let origin = area.as_point();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, area.width, area.height));
 
-        if max_width != self.buffer.area().width || max_height != self.buffer.area().height {
-            self.buffer.resize(Rect {
-                x: 0,
-                y: 0,
-                width: max_width,
-                height: max_height,
-            });
+        for element in self.iter_selected(selected_elements) {
+            draw_joined(
+                &mut buffer,
+                element.drawn_area().to_rect(origin),
+                |buffer| element.draw_to(buffer, origin, false, &None),
+            );
         }
 
-        self.buffer.content.fill(' '.into());
+        buffer
+    }
+}
 
-        for (i, element) in self.elements.iter().enumerate() {
-            let selected = selected_elements.contains(&i);
-            element.draw_to(
-                &mut self.buffer,
-                selected,
-                if selected { operation } else { &None },
+/// Draws `element` into `buffer`, whose top left cell is at `origin`, cutting
+/// off whatever doesn't fit. Elements that stick out of the buffer are drawn
+/// on their own first, relative to their own top left corner so that they
+/// keep their shape, but only as far as they overlap the buffer.
+fn draw_clipped(buffer: &mut Buffer, origin: Point, element: &Element, selected: bool) {
+    let area = element.drawn_area();
+    let buffer_area = Area::new(origin.x, origin.y, buffer.area.width, buffer.area.height);
+    if buffer_area.union(area) == buffer_area {
+        element.draw_to(buffer, origin, selected, &None);
+        return;
+    }
+
+    // The part of the element in view, relative to the element
+    let visible =
+        buffer_area
+            .to_rect(area.as_point())
+            .intersection(Rect::new(0, 0, area.width, area.height));
+    if visible.is_empty() {
+        return;
+    }
+
+    // Cells the element leaves alone keep this symbol
+    const UNTOUCHED: &str = "";
+    let mut scratch = Buffer::filled(visible, Cell::new(UNTOUCHED));
+    element.draw_to(&mut scratch, area.as_point(), selected, &None);
+
+    for position in visible.positions() {
+        let point = Point::new(area.x + position.x as i32, area.y + position.y as i32);
+        if let (Some(cell), Some(target)) = (
+            scratch
+                .cell(position)
+                .filter(|cell| cell.symbol() != UNTOUCHED),
+            point
+                .to_position(origin)
+                .and_then(|position| buffer.cell_mut(position)),
+        ) {
+            *target = cell.clone();
+        }
+    }
+}
+
+/// Whether aligning and distributing moves `el`. Connectors follow the
+/// elements they're bound to instead.
+fn is_aligned(el: &Element) -> bool {
+    !matches!(el.kind, ElementKind::Connector(..))
+}
+
+/// Works out the route of every connector, from where the elements its ends
+/// are bound to are now. This runs on every render, so routes always go
+/// around the elements where they currently are.
+fn route_connectors(elements: &mut VecDeque<Element>, routes: &mut Routes) {
+    let area_of = |id| {
+        elements
+            .iter()
+            .find_map(|el| el.find(id))
+            .map(Element::area)
+    };
+
+    // Connectors go around shapes and text, but may cross lines
+    let mut obstacles = vec![];
+    for el in elements.iter() {
+        el.visit(&mut |el| {
+            if !matches!(
+                el.kind,
+                ElementKind::Line(..)
+                    | ElementKind::Polyline(..)
+                    | ElementKind::Connector(..)
+                    | ElementKind::Group { .. }
+            ) {
+                obstacles.push(el.area());
+            }
+        });
+    }
+
+    let mut paths = HashMap::new();
+    for el in elements.iter() {
+        el.visit(&mut |el| {
+            if let ElementKind::Connector(connector) = &el.kind {
+                paths.insert(el.id, connector.route(area_of, &obstacles, routes));
+            }
+        });
+    }
+
+    for el in elements.iter_mut() {
+        el.visit_mut(&mut |el| {
+            if let (ElementKind::Connector(connector), Some(path)) =
+                (&mut el.kind, paths.remove(&el.id))
+            {
+                connector.path = path;
+            }
+        });
+    }
+}
+
+pub fn buffer_to_string(buffer: &Buffer) -> String {
+    let area = buffer.area();
+    let mut out_string = String::with_capacity(area.area() as usize);
+
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            out_string.push(
+                buffer
+                    .cell((x, y))
+                    .and_then(|c| c.symbol().chars().next())
+                    .unwrap_or_default(),
             );
         }
+        out_string.push('\n');
+    }
+
+    out_string
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::drawing::{Area, Connector, Endpoint, Side, StraightLine};
+
+    fn canvas(names: &[&str]) -> DrawingCanvas {
+        let mut canvas = DrawingCanvas::default();
+        for name in names {
+            canvas.push(ElementKind::Text {
+                area: Area::new(0, 0, 1, 1),
+                content: name.to_string(),
+                alignment: Default::default(),
+            });
+        }
+        canvas
+    }
+
+    fn contents(canvas: &DrawingCanvas) -> Vec<&str> {
+        canvas
+            .elements
+            .iter()
+            .filter_map(|el| match &el.kind {
+                ElementKind::Text { content, .. } => Some(content.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn ids(ids: &[u64]) -> HashSet<ElementId> {
+        ids.iter().copied().map(ElementId).collect()
+    }
+
+    #[test]
+    fn test_raise_and_lower() {
+        let mut canvas = canvas(&["a", "b", "c", "d"]);
+
+        canvas.raise(&ids(&[0, 3]));
+        assert_eq!(contents(&canvas), ["b", "a", "c", "d"]);
+
+        canvas.lower(&ids(&[0, 3]));
+        assert_eq!(contents(&canvas), ["a", "b", "d", "c"]);
+    }
+
+    #[test]
+    fn test_raise_to_top_and_lower_to_bottom() {
+        let mut canvas = canvas(&["a", "b", "c", "d"]);
+
+        canvas.raise_to_top(&ids(&[0, 2]));
+        assert_eq!(contents(&canvas), ["b", "d", "a", "c"]);
+
+        canvas.lower_to_bottom(&ids(&[2]));
+        assert_eq!(contents(&canvas), ["c", "b", "d", "a"]);
+    }
+
+    #[test]
+    fn test_move_layers() {
+        let mut canvas = canvas(&["a", "b", "c", "d"]);
+
+        canvas.move_layers(&ids(&[0]), ElementId(2));
+        assert_eq!(contents(&canvas), ["b", "c", "a", "d"]);
+
+        canvas.move_layers(&ids(&[0]), ElementId(1));
+        assert_eq!(contents(&canvas), ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_group_and_ungroup() {
+        let mut canvas = canvas(&["a", "b", "c", "d"]);
+
+        let group = canvas.group(&ids(&[0, 2])).unwrap();
+        assert_eq!(group, ElementId(4));
+        assert_eq!(contents(&canvas), ["b", "d"]);
+        assert_eq!(canvas.index_of(group), Some(1));
+
+        let released = canvas.ungroup(&HashSet::from([group]));
+        assert_eq!(released, ids(&[0, 2]));
+        assert_eq!(contents(&canvas), ["b", "a", "c", "d"]);
+    }
+
+    #[test]
+    fn test_render_selection() {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 3, 3),
+            style: Default::default(),
+        });
+        canvas.push(ElementKind::Text {
+            area: Area::new(20, 10, 2, 1),
+            content: "hi".into(),
+            alignment: Default::default(),
+        });
+
+        assert_eq!(
+            buffer_to_string(&canvas.render_selection(&ids(&[1]))),
+            "hi\n"
+        );
+    }
+
+    #[test]
+    fn test_align_and_distribute() {
+        let mut canvas = DrawingCanvas::default();
+        for area in [
+            Area::new(0, 0, 4, 2),
+            Area::new(10, 3, 6, 2),
+            Area::new(3, 9, 2, 2),
+        ] {
+            canvas.push(ElementKind::Box {
+                area,
+                style: Default::default(),
+            });
+        }
+        let areas = |canvas: &DrawingCanvas| {
+            canvas
+                .elements
+                .iter()
+                .map(Element::area)
+                .collect::<Vec<_>>()
+        };
+
+        canvas.align(&ids(&[0, 1, 2]), Align::Right);
+        assert_eq!(
+            areas(&canvas),
+            [
+                Area::new(12, 0, 4, 2),
+                Area::new(10, 3, 6, 2),
+                Area::new(14, 9, 2, 2)
+            ]
+        );
+
+        canvas.align(&ids(&[0, 1, 2]), Align::Center);
+        canvas.distribute(&ids(&[0, 1, 2]), Axis::Vertical);
+        assert_eq!(
+            areas(&canvas),
+            [
+                Area::new(11, 0, 4, 2),
+                Area::new(10, 4, 6, 2),
+                Area::new(12, 9, 2, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_connector_follows_and_detaches() {
+        let mut canvas = DrawingCanvas::default();
+        let a = canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 4, 3),
+            style: Default::default(),
+        });
+        let b = canvas.push(ElementKind::Box {
+            area: Area::new(10, 0, 4, 3),
+            style: Default::default(),
+        });
+        let connector = canvas.push(ElementKind::Connector(Connector::new(
+            Endpoint::Bound {
+                element: a,
+                side: Side::Right,
+            },
+            Endpoint::Bound {
+                element: b,
+                side: Side::Left,
+            },
+        )));
+        let path = |canvas: &DrawingCanvas| match &canvas.get(connector).unwrap().kind {
+            ElementKind::Connector(connector) => connector.path.clone(),
+            _ => vec![],
+        };
+
+        canvas.render(&HashSet::new(), &None, Area::new(0, 0, 18, 3));
+        assert_eq!(path(&canvas), [Point::new(4, 1), Point::new(9, 1)]);
+
+        let moved = Operation::Move {
+            origin: Point::new(11, 1),
+            second: Point::new(15, 1),
+        };
+        canvas.render(
+            &HashSet::from([b]),
+            &Some(moved.clone()),
+            Area::new(0, 0, 18, 3),
+        );
+        assert_eq!(path(&canvas), [Point::new(4, 1), Point::new(9, 1)]);
+        assert_eq!(
+            buffer_to_string(&canvas.buffer).lines().nth(1),
+            Some("│  │──────────│  │")
+        );
+
+        let el = canvas.get_mut(b).unwrap();
+        *el = moved.apply_transform(el).unwrap();
+        canvas.render(&HashSet::new(), &None, Area::new(0, 0, 18, 3));
+        assert_eq!(path(&canvas), [Point::new(4, 1), Point::new(13, 1)]);
+
+        canvas.remove(&HashSet::from([b]));
+        assert!(matches!(
+            &canvas.get(connector).unwrap().kind,
+            ElementKind::Connector(Connector {
+                to: Endpoint::Free { position },
+                ..
+            }) if *position == Point::new(13, 1)
+        ));
     }
-    pub fn to_string(&self) -> Vec<u8> {
-        let mut out_string = String::with_capacity(self.buffer.area().area() as usize);
 
-        for y in 0..self.buffer.area.height {
-            for x in 0..self.buffer.area.width {
-                out_string.push(
-                    self.buffer
-                        .cell((x, y))
-                        .and_then(|c| c.symbol().chars().nth(0))
-                        .unwrap_or_default(),
-                );
+    #[test]
+    fn test_ungroup_detaches_connectors() {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 4, 3),
+            style: Default::default(),
+        });
+        canvas.push(ElementKind::Box {
+            area: Area::new(0, 4, 4, 3),
+            style: Default::default(),
+        });
+        let group = canvas.group(&ids(&[0, 1])).unwrap();
+        let target = canvas.push(ElementKind::Box {
+            area: Area::new(10, 0, 4, 3),
+            style: Default::default(),
+        });
+        let connector = canvas.push(ElementKind::Connector(Connector::new(
+            Endpoint::Bound {
+                element: group,
+                side: Side::Right,
+            },
+            Endpoint::Bound {
+                element: target,
+                side: Side::Left,
+            },
+        )));
+        canvas.render(&HashSet::new(), &None, Area::new(0, 0, 14, 7));
+        let before = match &canvas.get(connector).unwrap().kind {
+            ElementKind::Connector(connector) => connector.path.clone(),
+            _ => vec![],
+        };
+
+        canvas.ungroup(&HashSet::from([group]));
+        let ElementKind::Connector(after) = &canvas.get(connector).unwrap().kind else {
+            panic!("not a connector");
+        };
+        assert_eq!(
+            after.from,
+            Endpoint::Free {
+                position: before[0]
             }
-            out_string.push('\n');
+        );
+        assert_eq!(after.to.bound_to(), Some(target));
+    }
+
+    #[test]
+    fn test_insert_nested_copies() {
+        let mut canvas = DrawingCanvas::default();
+        let a = canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 4, 3),
+            style: Default::default(),
+        });
+        let b = canvas.push(ElementKind::Box {
+            area: Area::new(10, 0, 4, 3),
+            style: Default::default(),
+        });
+        canvas.push(ElementKind::Connector(Connector::new(
+            Endpoint::Bound {
+                element: a,
+                side: Side::Right,
+            },
+            Endpoint::Bound {
+                element: b,
+                side: Side::Left,
+            },
+        )));
+        let group = canvas.group(&ids(&[0, 1, 2])).unwrap();
+
+        let copy = canvas.insert(
+            vec![canvas.get(group).unwrap().clone()],
+            Offset { x: 0, y: 5 },
+        );
+        canvas.ungroup(&HashSet::from([group, copy[0]]));
+
+        let mut all_ids = HashSet::new();
+        for el in &canvas.elements {
+            el.visit(&mut |el| assert!(all_ids.insert(el.id), "{:?} is taken", el.id));
         }
+        assert_eq!(canvas.elements.len(), 6);
+        // Each connector is bound to the two boxes just before it
+        for boxes in canvas.elements.make_contiguous().chunks(3) {
+            let ElementKind::Connector(connector) = &boxes[2].kind else {
+                panic!("not a connector");
+            };
+            assert_eq!(connector.from.bound_to(), Some(boxes[0].id));
+            assert_eq!(connector.to.bound_to(), Some(boxes[1].id));
+        }
+    }
+
+    #[test]
+    fn test_junctions() {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 5, 3),
+            style: Default::default(),
+        });
+        for (from, to) in [((4, 1), (8, 1)), ((2, 2), (2, 4))] {
+            canvas.push(ElementKind::Line(
+                StraightLine::new(from.into(), to.into()).unwrap(),
+            ));
+        }
+        canvas.render(&HashSet::new(), &None, Area::new(0, 0, 9, 5));
+
+        assert_eq!(
+            buffer_to_string(&canvas.buffer),
+            "┌───┐    \n│   ├────\n└─┬─┘    \n  │      \n  │      \n"
+        );
+    }
+
+    #[test]
+    fn test_grows_past_the_origin() {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(2, 1, 4, 3),
+            style: Default::default(),
+        });
+        let line = canvas.push(ElementKind::Line(
+            StraightLine::new(Point::new(0, 0), Point::new(2, 0)).unwrap(),
+        ));
+
+        let moved = Operation::Move {
+            origin: Point::new(1, 0),
+            second: Point::new(-3, -2),
+        };
+        let el = canvas.get_mut(line).unwrap();
+        *el = moved.apply_transform(el).unwrap();
+        canvas.render(&HashSet::new(), &None, Area::new(-4, -2, 10, 6));
+
+        assert_eq!(canvas.area, Area::new(-4, -2, 10, 6));
+        assert_eq!(
+            buffer_to_string(&canvas.buffer),
+            "───       \n          \n          \n      ┌──┐\n      │  │\n      └──┘\n"
+        );
+
+        // Only what's in view is drawn
+        canvas.render(&HashSet::new(), &None, Area::new(1, 1, 4, 2));
+        assert_eq!(canvas.area, Area::new(-4, -2, 10, 6));
+        assert_eq!(buffer_to_string(&canvas.buffer), " ┌──\n │  \n");
+    }
+
+    #[test]
+    fn test_clips_large_elements() {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(-10, 1, u16::MAX, u16::MAX),
+            style: Default::default(),
+        });
+        canvas.push(ElementKind::Text {
+            area: Area::new(-3, 2, 9, 1),
+            content: "abc".into(),
+            alignment: crate::drawing::TextAlignment::Center,
+        });
+        canvas.render(&HashSet::new(), &None, Area::new(0, 0, 4, 3));
+
+        assert_eq!(buffer_to_string(&canvas.buffer), "    \n────\nabc \n");
+    }
+
+    #[test]
+    fn test_ids_survive_removal() {
+        let mut canvas = canvas(&["a", "b", "c"]);
+
+        canvas.remove(&ids(&[1]));
+        let id = canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 2, 2),
+            style: Default::default(),
+        });
 
-        out_string.as_bytes().to_vec()
+        assert_eq!(id, ElementId(3));
+        assert_eq!(canvas.index_of(ElementId(2)), Some(1));
     }
 }
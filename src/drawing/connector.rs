@@ -0,0 +1,550 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use ratatui::{buffer::Buffer, style::Style};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter};
+
+use super::{render_path, Area, Arrowhead, ElementId, LineStyle, Point, StraightLine};
+
+/// What a turn costs an elbow route, in cells. Makes routes with fewer bends
+/// win over slightly shorter ones.
+const BEND_COST: u32 = 8;
+
+/// How far an elbow route may stray outside of the elements around it.
+const ROUTE_MARGIN: i32 = 2;
+
+/// How many cells an elbow route may search through at most. Past that,
+/// connectors take a plain L-shaped route instead.
+const MAX_ROUTE_CELLS: u32 = 1 << 16;
+
+/// How many sets of obstacles [`Routes`] keeps routes for. While elements are
+/// being moved, there's one for the canvas and one for the preview.
+const CACHED_OBSTACLE_SETS: usize = 4;
+
+/// A line whose ends can be bound to other elements, so that it follows them
+/// around when they're moved or resized.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Connector {
+    pub from: Endpoint,
+    pub to: Endpoint,
+    #[serde(default)]
+    pub style: LineStyle,
+    #[serde(default)]
+    pub start: Arrowhead,
+    #[serde(default)]
+    pub end: Arrowhead,
+    #[serde(default)]
+    pub routing: Routing,
+    /// The corners of the connector, from start to end, as last routed by the
+    /// canvas.
+    #[serde(skip)]
+    pub path: Vec<Point>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Endpoint {
+    /// Sticks to the middle of one side of an element.
+    Bound { element: ElementId, side: Side },
+    /// Stays where it is.
+    Free { position: Point },
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum Routing {
+    /// Runs in one of the eight directions a line can, from its start
+    /// towards its end.
+    Straight,
+    /// Runs around other elements, with right-angled bends.
+    #[default]
+    Elbow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Top,
+    Right,
+    Bottom,
+    Left,
+}
+
+impl Side {
+    /// The side of `area` that faces towards `position`.
+    pub fn facing(area: Area, position: Point) -> Self {
+        // Relative to the size of the area, so that a wide box is left through
+        // its top or bottom unless `position` is clearly to the side of it
+        let dx = (position.x as f32 - area.x as f32 - area.width as f32 / 2.) / area.width as f32;
+        let dy = (position.y as f32 - area.y as f32 - area.height as f32 / 2.) / area.height as f32;
+
+        match (dx.abs() > dy.abs(), dx > 0., dy > 0.) {
+            (true, true, _) => Self::Right,
+            (true, false, _) => Self::Left,
+            (false, _, true) => Self::Bottom,
+            (false, _, false) => Self::Top,
+        }
+    }
+
+    /// The cell just outside the middle of this side of `area`.
+    pub fn anchor(self, area: Area) -> Point {
+        let (center_x, center_y) = (
+            area.x + area.width as i32 / 2,
+            area.y + area.height as i32 / 2,
+        );
+        match self {
+            Self::Top => Point::new(center_x, area.y - 1),
+            Self::Right => Point::new(area.right(), center_y),
+            Self::Bottom => Point::new(center_x, area.bottom()),
+            Self::Left => Point::new(area.x - 1, center_y),
+        }
+    }
+
+    /// The direction leading away from this side, as an index into [`STEPS`].
+    fn outwards(self) -> usize {
+        match self {
+            Self::Top => 0,
+            Self::Right => 1,
+            Self::Bottom => 2,
+            Self::Left => 3,
+        }
+    }
+}
+
+/// Up, right, down and left.
+const STEPS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+impl Endpoint {
+    pub fn bound_to(&self) -> Option<ElementId> {
+        match self {
+            Self::Bound { element, .. } => Some(*element),
+            Self::Free { .. } => None,
+        }
+    }
+
+    /// Where the end is, given a way to look up the area of the element it's
+    /// bound to. Falls back to `last` if that element can't be found.
+    fn resolve(
+        &self,
+        area_of: impl Fn(ElementId) -> Option<Area>,
+        last: Option<Point>,
+    ) -> Option<Point> {
+        match self {
+            Self::Bound { element, side } => {
+                area_of(*element).map(|area| side.anchor(area)).or(last)
+            }
+            Self::Free { position } => Some(*position),
+        }
+    }
+
+    fn map(self, f: impl Fn(Point) -> Point) -> Self {
+        match self {
+            Self::Free { position } => Self::Free {
+                position: f(position),
+            },
+            bound => bound,
+        }
+    }
+}
+
+impl Connector {
+    pub fn new(from: Endpoint, to: Endpoint) -> Self {
+        Self {
+            from,
+            to,
+            style: LineStyle::default(),
+            start: Arrowhead::None,
+            end: Arrowhead::None,
+            routing: Routing::default(),
+            path: vec![],
+        }
+    }
+
+    /// Moves the free ends, and the route, through `f`. Bound ends stay with
+    /// their elements.
+    pub fn map(&self, f: impl Fn(Point) -> Point) -> Self {
+        Self {
+            from: self.from.map(&f),
+            to: self.to.map(&f),
+            path: self.path.iter().copied().map(&f).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// Works out the route of the connector between its ends. Elbow routes
+    /// go around `obstacles` where they can, and are looked up in `routes`
+    /// if they've been worked out before.
+    pub fn route(
+        &self,
+        area_of: impl Fn(ElementId) -> Option<Area>,
+        obstacles: &[Area],
+        routes: &mut Routes,
+    ) -> Vec<Point> {
+        let from = self.from.resolve(&area_of, self.path.first().copied());
+        let to = self.to.resolve(&area_of, self.path.last().copied());
+        let (Some(from), Some(to)) = (from, to) else {
+            return vec![];
+        };
+
+        match self.routing {
+            Routing::Straight => {
+                StraightLine::new(from, to).map_or(vec![from], |line| vec![line.from, line.to])
+            }
+            Routing::Elbow => {
+                let side = |end: &Endpoint| match end {
+                    Endpoint::Bound { side, .. } => Some(*side),
+                    Endpoint::Free { .. } => None,
+                };
+                routes
+                    .route_around(
+                        from,
+                        side(&self.from).map(Side::outwards),
+                        to,
+                        side(&self.to).map(|side| (side.outwards() + 2) % 4),
+                        obstacles,
+                    )
+                    .unwrap_or_else(|| {
+                        let mut path = vec![from, Point::new(to.x, from.y), to];
+                        path.dedup();
+                        path
+                    })
+            }
+        }
+    }
+
+    /// Frees the ends bound to any of `elements`, leaving them where they are.
+    pub fn detach(&mut self, elements: &HashSet<ElementId>) {
+        for (end, last) in [
+            (&mut self.from, self.path.first()),
+            (&mut self.to, self.path.last()),
+        ] {
+            if let Some(position) = last.filter(|_| {
+                end.bound_to()
+                    .is_some_and(|element| elements.contains(&element))
+            }) {
+                *end = Endpoint::Free {
+                    position: *position,
+                };
+            }
+        }
+    }
+
+    /// Binds the ends to the new IDs of copied elements. Ends bound to
+    /// elements that weren't copied along are freed.
+    pub fn rebind(&mut self, ids: &HashMap<ElementId, ElementId>) {
+        for end in [&mut self.from, &mut self.to] {
+            if let Endpoint::Bound { element, .. } = end {
+                if let Some(id) = ids.get(element) {
+                    *element = *id;
+                }
+            }
+        }
+        let unbound = [self.from, self.to]
+            .iter()
+            .filter_map(Endpoint::bound_to)
+            .filter(|element| !ids.values().any(|id| id == element))
+            .collect();
+        self.detach(&unbound);
+    }
+
+    pub fn area(&self) -> Area {
+        self.path
+            .iter()
+            .map(|position| Area::new(position.x, position.y, 1, 1))
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default()
+    }
+
+    pub fn render_to(&self, buffer: &mut Buffer, origin: Point, style: Style) {
+        render_path(
+            &self.path, self.style, self.start, self.end, buffer, origin, style,
+        );
+    }
+}
+
+/// Elbow routes worked out before, for each set of obstacles they go around.
+/// Routing only has to run again for connectors whose ends moved, or when an
+/// obstacle did.
+#[derive(Default)]
+pub struct Routes {
+    cached: HashMap<Vec<Area>, HashMap<RouteEnds, Option<Vec<Point>>>>,
+}
+
+/// Where a route starts and ends, and the directions it leaves and enters
+/// in.
+type RouteEnds = (Point, Option<usize>, Point, Option<usize>);
+
+impl Routes {
+    /// Like [`route_around`], but only runs the search for ends and obstacles
+    /// it hasn't seen before.
+    fn route_around(
+        &mut self,
+        from: Point,
+        leaving: Option<usize>,
+        to: Point,
+        entering: Option<usize>,
+        obstacles: &[Area],
+    ) -> Option<Vec<Point>> {
+        if !self.cached.contains_key(obstacles) && self.cached.len() >= CACHED_OBSTACLE_SETS {
+            self.cached.clear();
+        }
+        self.cached
+            .entry(obstacles.to_vec())
+            .or_default()
+            .entry((from, leaving, to, entering))
+            .or_insert_with(|| route_around(from, leaving, to, entering, obstacles))
+            .clone()
+    }
+}
+
+/// Finds a route from `from` to `to` that doesn't cross `obstacles`, with as
+/// few bends as possible. It can be made to leave `from` and enter `to` in a
+/// given direction, as an index into [`STEPS`]. Returns the start, the
+/// corners and the end of the route.
+///
+/// The search starts out close around the two ends, and only widens, up to
+/// all of the obstacles, if there's no route there. It gives up once it
+/// would have to look through more than [`MAX_ROUTE_CELLS`].
+fn route_around(
+    from: Point,
+    leaving: Option<usize>,
+    to: Point,
+    entering: Option<usize>,
+    obstacles: &[Area],
+) -> Option<Vec<Point>> {
+    let all = grow(
+        obstacles
+            .iter()
+            .fold(Area::between(from, to), |bounds, area| bounds.union(*area)),
+        ROUTE_MARGIN,
+    );
+    let mut margin = ROUTE_MARGIN;
+    loop {
+        let mut bounds = grow(Area::between(from, to), margin);
+        let everything = bounds.union(all) == bounds;
+        if everything {
+            bounds = all;
+        }
+        if bounds.area() > MAX_ROUTE_CELLS {
+            return None;
+        }
+        let path = route_within(from, leaving, to, entering, obstacles, bounds);
+        if path.is_some() || everything {
+            return path;
+        }
+        margin *= 2;
+    }
+}
+
+/// `area`, with `margin` more cells on every side.
+fn grow(area: Area, margin: i32) -> Area {
+    let by = u16::try_from(margin * 2).unwrap_or(u16::MAX);
+    Area::new(
+        area.x - margin,
+        area.y - margin,
+        area.width.saturating_add(by),
+        area.height.saturating_add(by),
+    )
+}
+
+/// Searches for a route within `bounds` only.
+fn route_within(
+    from: Point,
+    leaving: Option<usize>,
+    to: Point,
+    entering: Option<usize>,
+    obstacles: &[Area],
+    bounds: Area,
+) -> Option<Vec<Point>> {
+    let (left, top) = (bounds.x, bounds.y);
+    let (width, height) = (bounds.width as i32, bounds.height as i32);
+
+    let cell = |x: i32, y: i32| {
+        ((left..left + width).contains(&x) && (top..top + height).contains(&y))
+            .then(|| ((y - top) * width + (x - left)) as usize)
+    };
+    let position = |cell: usize| Point::new(cell as i32 % width + left, cell as i32 / width + top);
+
+    let mut blocked = vec![false; bounds.area() as usize];
+    for area in obstacles.iter().filter(|area| area.intersects(bounds)) {
+        for y in area.top().max(top)..area.bottom().min(top + height) {
+            for x in area.left().max(left)..area.right().min(left + width) {
+                if let Some(cell) = cell(x, y) {
+                    blocked[cell] = true;
+                }
+            }
+        }
+    }
+    let (start, goal) = (cell(from.x, from.y)?, cell(to.x, to.y)?);
+    blocked[start] = false;
+    blocked[goal] = false;
+
+    // Dijkstra over (cell, direction it was entered in)
+    let mut costs = vec![u32::MAX; blocked.len() * 4];
+    let mut previous = vec![usize::MAX; blocked.len() * 4];
+    let mut queue = BinaryHeap::new();
+    for direction in 0..4 {
+        if leaving.is_none_or(|leaving| leaving == direction) {
+            costs[start * 4 + direction] = 0;
+            queue.push(Reverse((0, start * 4 + direction)));
+        }
+    }
+
+    let end = loop {
+        let Reverse((cost, state)) = queue.pop()?;
+        if cost > costs[state] {
+            continue;
+        }
+        let (current, direction) = (state / 4, state % 4);
+        if current == goal {
+            break state;
+        }
+
+        let Point { x, y } = position(current);
+        for (next_direction, (dx, dy)) in STEPS.iter().enumerate() {
+            let Some(next) = cell(x + dx, y + dy).filter(|next| !blocked[*next]) else {
+                continue;
+            };
+            let mut next_cost = cost + 1;
+            if next_direction != direction {
+                next_cost += BEND_COST;
+            }
+            // Running alongside the element at the end looks worse than a
+            // detour around it
+            if next == goal && entering.is_some_and(|entering| entering != next_direction) {
+                next_cost += BEND_COST * 3;
+            }
+            let next_state = next * 4 + next_direction;
+            if next_cost < costs[next_state] {
+                costs[next_state] = next_cost;
+                previous[next_state] = state;
+                queue.push(Reverse((next_cost, next_state)));
+            }
+        }
+    };
+
+    // Walk back to the start, keeping the cells where the route turns
+    let mut path = vec![to];
+    let mut state = end;
+    while previous[state] != usize::MAX {
+        let before = previous[state];
+        if before / 4 == start || before % 4 != state % 4 {
+            path.push(position(before / 4));
+        }
+        state = before;
+    }
+    path.reverse();
+    path.dedup();
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_facing() {
+        let area = Area::new(10, 10, 10, 4);
+        assert_eq!(Side::facing(area, Point::new(30, 12)), Side::Right);
+        assert_eq!(Side::facing(area, Point::new(0, 12)), Side::Left);
+        assert_eq!(Side::facing(area, Point::new(16, 0)), Side::Top);
+        assert_eq!(Side::facing(area, Point::new(16, 20)), Side::Bottom);
+
+        assert_eq!(Side::Top.anchor(area), Point::new(15, 9));
+        assert_eq!(Side::Right.anchor(area), Point::new(20, 12));
+    }
+
+    #[test]
+    fn test_elbow_route_avoids_obstacles() {
+        let obstacle = Area::new(4, 0, 3, 3);
+        let path = route_around(
+            Point::new(0, 1),
+            Some(Side::Right.outwards()),
+            Point::new(10, 1),
+            Some(Side::Left.outwards()),
+            &[obstacle],
+        )
+        .unwrap();
+
+        assert_eq!(path.first(), Some(&Point::new(0, 1)));
+        assert_eq!(path.last(), Some(&Point::new(10, 1)));
+        assert_eq!(path.len(), 6);
+        for ends in path.windows(2) {
+            let line = StraightLine::new(ends[0], ends[1]).unwrap();
+            assert!(!line.area().intersects(obstacle));
+        }
+    }
+
+    #[test]
+    fn test_elbow_route_widens_search() {
+        // A wall that's too tall to get around close to the ends
+        let wall = Area::new(4, -20, 3, 50);
+        let mut routes = Routes::default();
+        let path = routes
+            .route_around(Point::new(0, 5), None, Point::new(10, 5), None, &[wall])
+            .unwrap();
+
+        assert_eq!(path.first(), Some(&Point::new(0, 5)));
+        assert_eq!(path.last(), Some(&Point::new(10, 5)));
+        for ends in path.windows(2) {
+            let line = StraightLine::new(ends[0], ends[1]).unwrap();
+            assert!(!line.area().intersects(wall));
+        }
+        assert_eq!(routes.cached[&vec![wall]].len(), 1);
+    }
+
+    #[test]
+    fn test_elbow_route_gives_up_on_huge_searches() {
+        // The start is walled in, and something far away makes the area
+        // around all obstacles too large to search
+        let obstacles = [
+            Area::new(-2, -2, 5, 1),
+            Area::new(-2, 2, 5, 1),
+            Area::new(-2, -1, 1, 3),
+            Area::new(2, -1, 1, 3),
+            Area::new(100_000, 100_000, 10, 10),
+        ];
+        let (from, to) = (Point::new(0, 0), Point::new(10, 4));
+        assert_eq!(route_around(from, None, to, None, &obstacles), None);
+
+        let mut connector = Connector::new(
+            Endpoint::Free { position: from },
+            Endpoint::Free { position: to },
+        );
+        connector.routing = Routing::Elbow;
+        assert_eq!(
+            connector.route(|_| None, &obstacles, &mut Routes::default()),
+            [from, Point::new(10, 0), to]
+        );
+    }
+
+    #[test]
+    fn test_elbow_corners() {
+        let mut connector = Connector::new(
+            Endpoint::Free {
+                position: Point::new(0, 0),
+            },
+            Endpoint::Free {
+                position: Point::new(4, 2),
+            },
+        );
+        connector.style = LineStyle::Thick;
+        connector.end = Arrowhead::Triangle;
+        connector.path = connector.route(|_| None, &[], &mut Routes::default());
+        assert_eq!(
+            connector.path,
+            [Point::new(0, 0), Point::new(0, 2), Point::new(4, 2)]
+        );
+
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 5, 3));
+        connector.render_to(&mut buffer, Point::ORIGIN, Style::new());
+        assert_eq!(
+            crate::drawing::buffer_to_string(&buffer),
+            "┃    \n┃    \n┗━━━▶\n"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::{collections::VecDeque, fs, path::Path};
+
+use color_eyre::{eyre::eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{Element, ElementId, Guide};
+
+/// Version of the native file format written by [`Document::save`].
+///
+/// Bump this whenever the serialized shape of an [`Element`] changes in a way
+/// older versions of sketshy couldn't read.
+pub const DOCUMENT_VERSION: u32 = 2;
+
+/// The native sketshy file format: every element of the canvas, in layer
+/// order, serialized as pretty-printed JSON so it diffs nicely in git.
+#[derive(Serialize, Deserialize)]
+pub struct Document {
+    pub version: u32,
+    pub elements: VecDeque<Element>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guides: Vec<Guide>,
+}
+
+impl Document {
+    pub fn new(elements: VecDeque<Element>, guides: Vec<Guide>) -> Self {
+        Self {
+            version: DOCUMENT_VERSION,
+            elements,
+            guides,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let mut document: Self = serde_json::from_str(&fs::read_to_string(path)?)?;
+
+        if document.version > DOCUMENT_VERSION {
+            return Err(eyre!(
+                "Document version {} is newer than the supported version {}",
+                document.version,
+                DOCUMENT_VERSION
+            ));
+        }
+
+        // Version 1 didn't store element IDs yet.
+        if document.version < 2 {
+            for (i, element) in document.elements.iter_mut().enumerate() {
+                element.id = ElementId(i as u64);
+            }
+        }
+
+        Ok(document)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut json = serde_json::to_string_pretty(self)?;
+        json.push('\n');
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::drawing::{
+        Area, BorderStyle, BoxStyle, Connector, DrawingCanvas, ElementKind, Endpoint, Point,
+        Shadow, Side, StraightLine, TextAlignment,
+    };
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(1, 2, 10, 5),
+            style: BoxStyle {
+                border: BorderStyle::Rounded,
+                title: "API".into(),
+                title_alignment: TextAlignment::Center,
+                shadow: Shadow::Light,
+            },
+        });
+        canvas.push(ElementKind::Text {
+            area: Area::new(3, 3, 6, 1),
+            content: "Hello\nWorld".into(),
+            alignment: TextAlignment::Right,
+        });
+        let line = canvas.push(ElementKind::Line(
+            StraightLine::new(Point::new(4, 8), Point::new(12, 8)).unwrap(),
+        ));
+        canvas.push(ElementKind::Connector(Connector::new(
+            Endpoint::Bound {
+                element: line,
+                side: Side::Bottom,
+            },
+            Endpoint::Free {
+                position: Point::new(20, 20),
+            },
+        )));
+        let document = Document::new(canvas.elements, vec![Guide::Horizontal { y: -3 }]);
+
+        let json = serde_json::to_string_pretty(&document)?;
+        let parsed: Document = serde_json::from_str(&json)?;
+
+        assert_eq!(parsed.version, DOCUMENT_VERSION);
+        assert_eq!(parsed.guides, document.guides);
+        assert_eq!(json, serde_json::to_string_pretty(&parsed)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() -> Result<()> {
+        let path = std::env::temp_dir().join("sketshy_test_newer_version.json");
+        fs::write(
+            &path,
+            format!(r#"{{"version": {}, "elements": []}}"#, DOCUMENT_VERSION + 1),
+        )?;
+
+        assert!(Document::open(&path).is_err());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_1_gets_ids() -> Result<()> {
+        let path = std::env::temp_dir().join("sketshy_test_version_1.json");
+        fs::write(
+            &path,
+            r#"{"version": 1, "elements": [
+                {"type": "box", "area": {"x": 0, "y": 0, "width": 2, "height": 2}},
+                {"type": "box", "area": {"x": 4, "y": 0, "width": 2, "height": 2}}
+            ]}"#,
+        )?;
+
+        let document = Document::open(&path)?;
+        fs::remove_file(path)?;
+
+        assert_eq!(
+            document.elements.iter().map(|el| el.id).collect::<Vec<_>>(),
+            [ElementId(0), ElementId(1)]
+        );
+        Ok(())
+    }
+}
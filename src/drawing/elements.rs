@@ -1,40 +1,479 @@
 use ratatui::{
-    layout::{Offset, Position, Rect},
+    buffer::{Buffer, Cell},
+    layout::{Alignment, Offset, Rect},
     style::Style,
-    widgets::{Block, Clear, Paragraph, Widget},
+    symbols::border,
+    text::Line,
 };
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
 
-use super::Operation;
+use super::{Area, Connector, Operation, Point};
 use crate::app::color_scheme;
 
-#[derive(Clone)]
-pub enum Element {
-    Box { area: Rect },
-    Text { area: Rect, content: String },
+/// Identifies an element for as long as it exists, no matter where it's
+/// moved in the layer stack.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct ElementId(pub u64);
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Element {
+    #[serde(default)]
+    pub id: ElementId,
+    #[serde(flatten)]
+    pub kind: ElementKind,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ElementKind {
+    Box {
+        area: Area,
+        #[serde(default)]
+        style: BoxStyle,
+    },
+    Ellipse {
+        area: Area,
+    },
+    Diamond {
+        area: Area,
+    },
+    Text {
+        area: Area,
+        content: String,
+        #[serde(default)]
+        alignment: TextAlignment,
+    },
     Line(StraightLine),
+    Polyline(Polyline),
+    Connector(Connector),
+    Paint {
+        pixels: Vec<Pixel>,
+    },
+    Group {
+        children: Vec<Element>,
+    },
+}
+
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoxStyle {
+    pub border: BorderStyle,
+    /// Drawn into the top border. Empty if the box has no title.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    pub title_alignment: TextAlignment,
+    pub shadow: Shadow,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum BorderStyle {
+    #[default]
+    Plain,
+    Rounded,
+    Double,
+    Thick,
+    Dashed,
+    Ascii,
+}
+
+impl BorderStyle {
+    fn set(self) -> border::Set {
+        match self {
+            Self::Plain => border::PLAIN,
+            Self::Rounded => border::ROUNDED,
+            Self::Double => border::DOUBLE,
+            Self::Thick => border::THICK,
+            Self::Dashed => border::Set {
+                vertical_left: "┆",
+                vertical_right: "┆",
+                horizontal_top: "┄",
+                horizontal_bottom: "┄",
+                ..border::PLAIN
+            },
+            Self::Ascii => border::Set {
+                top_left: "+",
+                top_right: "+",
+                bottom_left: "+",
+                bottom_right: "+",
+                vertical_left: "|",
+                vertical_right: "|",
+                horizontal_top: "-",
+                horizontal_bottom: "-",
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl From<TextAlignment> for Alignment {
+    fn from(alignment: TextAlignment) -> Self {
+        match alignment {
+            TextAlignment::Left => Alignment::Left,
+            TextAlignment::Center => Alignment::Center,
+            TextAlignment::Right => Alignment::Right,
+        }
+    }
+}
+
+/// A shadow cast to the bottom right of a box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum Shadow {
+    #[default]
+    None,
+    Light,
+    Medium,
+    Full,
+}
+
+impl Shadow {
+    fn symbol(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::Light => Some("░"),
+            Self::Medium => Some("▒"),
+            Self::Full => Some("█"),
+        }
+    }
+
+    /// How far the shadow sticks out to the right and below its box.
+    fn offset(self) -> Offset {
+        match self {
+            Self::None => Offset { x: 0, y: 0 },
+            _ => Offset { x: 2, y: 1 },
+        }
+    }
+
+    fn render_to(self, area: Rect, buffer: &mut Buffer, style: Style) {
+        let Some(symbol) = self.symbol() else {
+            return;
+        };
+        let Offset { x: dx, y: dy } = self.offset();
+        let (dx, dy) = (dx as u16, dy as u16);
+
+        for y in area.top() + dy..area.bottom() + dy {
+            for x in area.right()..area.right() + dx {
+                set_symbol(buffer, x, y, symbol, style);
+            }
+        }
+        for x in area.left() + dx..area.right() {
+            for y in area.bottom()..area.bottom() + dy {
+                set_symbol(buffer, x, y, symbol, style);
+            }
+        }
+    }
+}
+
+/// Steps `by` variants through an enum, wrapping around at either end.
+pub fn cycle<T: IntoEnumIterator + PartialEq>(value: T, by: isize) -> T {
+    let variants = T::iter().collect::<Vec<_>>();
+    let index = variants.iter().position(|v| *v == value).unwrap_or(0);
+    let index = (index as isize + by).rem_euclid(variants.len() as isize) as usize;
+    variants.into_iter().nth(index).unwrap_or(value)
+}
+
+/// A single painted cell of a [`ElementKind::Paint`] layer.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pixel {
+    pub x: i32,
+    pub y: i32,
+    pub symbol: char,
 }
 
 impl Element {
+    pub fn new(id: ElementId, kind: ElementKind) -> Self {
+        Self { id, kind }
+    }
+
+    pub fn name(&self) -> String {
+        self.kind.name()
+    }
+
+    pub fn area(&self) -> Area {
+        self.kind.area()
+    }
+
+    pub fn drawn_area(&self) -> Area {
+        self.kind.drawn_area()
+    }
+
+    /// Finds the element with the given ID, which is either this one or one
+    /// nested in it.
+    pub fn find(&self, id: ElementId) -> Option<&Element> {
+        if self.id == id {
+            return Some(self);
+        }
+        self.kind.children().iter().find_map(|child| child.find(id))
+    }
+
+    /// Calls `f` with this element and every element nested in it.
+    pub fn visit(&self, f: &mut impl FnMut(&Element)) {
+        f(self);
+        for child in self.kind.children() {
+            child.visit(f);
+        }
+    }
+
+    /// Calls `f` with this element and every element nested in it.
+    pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut Element)) {
+        f(self);
+        if let ElementKind::Group { children } = &mut self.kind {
+            for child in children {
+                child.visit_mut(f);
+            }
+        }
+    }
+
+    pub(crate) fn draw_to(
+        &self,
+        buffer: &mut ratatui::prelude::Buffer,
+        origin: Point,
+        selected: bool,
+        operation: &Option<Operation>,
+    ) {
+        let trans = operation.clone().and_then(|op| op.apply_transform(self));
+        trans
+            .as_ref()
+            .unwrap_or(self)
+            .kind
+            .render_to(buffer, origin, selected);
+    }
+}
+
+impl ElementKind {
     pub fn name(&self) -> String {
         match self {
             Self::Box { .. } => "Box".into(),
+            Self::Ellipse { .. } => "Ellipse".into(),
+            Self::Diamond { .. } => "Diamond".into(),
             Self::Text { content, .. } => format!("Text \"{}\"", content),
             Self::Line(..) => "Line".into(),
+            Self::Polyline(..) => "Polyline".into(),
+            Self::Connector(..) => "Connector".into(),
+            Self::Paint { .. } => "Paint".into(),
+            Self::Group { children } => format!("Group ({})", children.len()),
         }
     }
 
-    pub fn area(&self) -> Rect {
+    pub fn area(&self) -> Area {
         match self {
-            Self::Box { area } | Self::Text { area, .. } => *area,
+            Self::Box { area, .. }
+            | Self::Ellipse { area }
+            | Self::Diamond { area }
+            | Self::Text { area, .. } => *area,
             Self::Line(line) => line.area(),
+            Self::Polyline(polyline) => polyline.area(),
+            Self::Connector(connector) => connector.area(),
+            Self::Paint { pixels } => pixels
+                .iter()
+                .map(|pixel| Area::new(pixel.x, pixel.y, 1, 1))
+                .reduce(|a, b| a.union(b))
+                .unwrap_or_default(),
+            Self::Group { children } => children
+                .iter()
+                .map(Element::area)
+                .reduce(|a, b| a.union(b))
+                .unwrap_or_default(),
         }
     }
 
-    pub(crate) fn draw_to(
+    /// The area the element covers when drawn, which is more than its
+    /// [`area`](Self::area) if it casts a shadow.
+    pub fn drawn_area(&self) -> Area {
+        match self {
+            Self::Box { area, style } => {
+                let Offset { x, y } = style.shadow.offset();
+                area.union(area.offset(Offset { x, y }))
+            }
+            Self::Group { children } => children
+                .iter()
+                .map(|child| child.kind.drawn_area())
+                .reduce(|a, b| a.union(b))
+                .unwrap_or_default(),
+            _ => self.area(),
+        }
+    }
+
+    /// Returns the elements this one is made of, if it's a group.
+    pub fn children(&self) -> &[Element] {
+        match self {
+            Self::Group { children } => children,
+            _ => &[],
+        }
+    }
+
+    /// Stamps `symbol` into the cell at `position`, if this is a paint layer.
+    pub fn paint(&mut self, position: Point, symbol: char) {
+        if let Self::Paint { pixels } = self {
+            match pixels
+                .iter_mut()
+                .find(|pixel| pixel.x == position.x && pixel.y == position.y)
+            {
+                Some(pixel) => pixel.symbol = symbol,
+                None => pixels.push(Pixel {
+                    x: position.x,
+                    y: position.y,
+                    symbol,
+                }),
+            }
+        }
+    }
+
+    /// Clears the cell at `position` from this paint layer, or from the paint
+    /// layers inside of this group. Returns whether anything was erased.
+    pub fn erase(&mut self, position: Point) -> bool {
+        match self {
+            Self::Paint { pixels } => {
+                let len = pixels.len();
+                pixels.retain(|pixel| pixel.x != position.x || pixel.y != position.y);
+                pixels.len() != len
+            }
+            Self::Group { children } => {
+                let mut erased = false;
+                for child in children {
+                    erased |= child.kind.erase(position);
+                }
+                erased
+            }
+            _ => false,
+        }
+    }
+
+    /// Stretches the element, so that what was at `from` ends up filling `to`.
+    pub fn scale(&self, from: Area, to: Area) -> Self {
+        match self {
+            Self::Box { area, style } => Self::Box {
+                area: scale_rect(*area, from, to),
+                style: style.clone(),
+            },
+            Self::Ellipse { area } => Self::Ellipse {
+                area: scale_rect(*area, from, to),
+            },
+            Self::Diamond { area } => Self::Diamond {
+                area: scale_rect(*area, from, to),
+            },
+            Self::Text {
+                area,
+                content,
+                alignment,
+            } => Self::Text {
+                area: scale_rect(*area, from, to),
+                content: content.clone(),
+                alignment: *alignment,
+            },
+            Self::Line(line) => {
+                let (start, end) = (
+                    scale_position(line.from, from, to),
+                    scale_position(line.to, from, to),
+                );
+                Self::Line(
+                    StraightLine::new(start, end)
+                        .map(|new| new.styled_like(line, false))
+                        .unwrap_or(StraightLine {
+                            from: start,
+                            to: end,
+                            ..line.clone()
+                        }),
+                )
+            }
+            Self::Polyline(polyline) => {
+                Self::Polyline(polyline.map(|position| scale_position(position, from, to)))
+            }
+            Self::Connector(connector) => {
+                Self::Connector(connector.map(|position| scale_position(position, from, to)))
+            }
+            Self::Paint { pixels } => Self::Paint {
+                pixels: pixels
+                    .iter()
+                    .map(|pixel| {
+                        let Point { x, y } = scale_position(Point::new(pixel.x, pixel.y), from, to);
+                        Pixel {
+                            x,
+                            y,
+                            symbol: pixel.symbol,
+                        }
+                    })
+                    .collect(),
+            },
+            Self::Group { children } => Self::Group {
+                children: children
+                    .iter()
+                    .map(|child| Element::new(child.id, child.kind.scale(from, to)))
+                    .collect(),
+            },
+        }
+    }
+
+    pub fn offset(&self, offset: Offset) -> Self {
+        match self {
+            Self::Box { area, style } => Self::Box {
+                area: area.offset(offset),
+                style: style.clone(),
+            },
+            Self::Ellipse { area } => Self::Ellipse {
+                area: area.offset(offset),
+            },
+            Self::Diamond { area } => Self::Diamond {
+                area: area.offset(offset),
+            },
+            Self::Text {
+                area,
+                content,
+                alignment,
+            } => Self::Text {
+                area: area.offset(offset),
+                content: content.clone(),
+                alignment: *alignment,
+            },
+            Self::Line(line) => Self::Line(line.offset(offset)),
+            Self::Polyline(polyline) => {
+                Self::Polyline(polyline.map(|position| position.offset(offset)))
+            }
+            Self::Connector(connector) => {
+                Self::Connector(connector.map(|position| position.offset(offset)))
+            }
+            Self::Paint { pixels } => Self::Paint {
+                pixels: pixels
+                    .iter()
+                    .map(|pixel| Pixel {
+                        x: pixel.x + offset.x,
+                        y: pixel.y + offset.y,
+                        symbol: pixel.symbol,
+                    })
+                    .collect(),
+            },
+            Self::Group { children } => Self::Group {
+                children: children
+                    .iter()
+                    .map(|child| Element::new(child.id, child.kind.offset(offset)))
+                    .collect(),
+            },
+        }
+    }
+
+    /// Draws the element to `buffer`, whose top left cell is at `origin` on
+    /// the canvas.
+    pub(crate) fn render_to(
         &self,
         buffer: &mut ratatui::prelude::Buffer,
+        origin: Point,
         selected: bool,
-        operation: &Option<Operation>,
     ) {
         let style = if selected {
             Style::new()
@@ -44,34 +483,473 @@ impl Element {
             Style::new().fg(color_scheme::FG_BASE)
         };
 
-        let trans = operation.clone().and_then(|op| op.apply_transform(self));
-        let el = trans.as_ref().unwrap_or(self);
+        match self {
+            Self::Box {
+                area,
+                style: box_style,
+            } => {
+                let area = area.to_rect(origin);
+                box_style.shadow.render_to(area, buffer, style);
+                render_box(area, box_style, buffer, style);
+            }
+            Self::Ellipse { area } => render_ellipse(area.to_rect(origin), buffer, style),
+            Self::Diamond { area } => render_diamond(area.to_rect(origin), buffer, style),
+            Self::Text {
+                area,
+                content,
+                alignment,
+            } => render_text(area.to_rect(origin), content, *alignment, buffer, style),
+            Self::Line(line) => {
+                line.render_to(buffer, origin, style);
+            }
+            Self::Polyline(polyline) => polyline.render_to(buffer, origin, style),
+            Self::Connector(connector) => connector.render_to(buffer, origin, style),
+            Self::Paint { pixels } => {
+                for pixel in pixels {
+                    if let Some(cell) = cell_at(buffer, origin, Point::new(pixel.x, pixel.y)) {
+                        cell.set_char(pixel.symbol);
+                        cell.set_style(style);
+                    }
+                }
+            }
+            Self::Group { children } => {
+                for child in children {
+                    child.kind.render_to(buffer, origin, selected);
+                }
+            }
+        }
+    }
+}
+
+/// The cell `point` is drawn to, in a buffer whose top left cell is at
+/// `origin`.
+fn cell_at(buffer: &mut Buffer, origin: Point, point: Point) -> Option<&mut Cell> {
+    point
+        .to_position(origin)
+        .and_then(|position| buffer.cell_mut(position))
+}
+
+fn set_symbol(buffer: &mut Buffer, x: u16, y: u16, symbol: &str, style: Style) {
+    if let Some(cell) = buffer.cell_mut((x, y)) {
+        cell.set_symbol(symbol);
+        cell.set_style(style);
+    }
+}
+
+/// Clears the cells from `left` to `right` on row `y`, so that the shape
+/// covers whatever is behind it, like a box does.
+fn clear_span(buffer: &mut Buffer, left: u16, right: u16, y: u16, style: Style) {
+    let right = right.min(buffer.area.right().saturating_sub(1));
+    for x in left.max(buffer.area.left())..=right {
+        if let Some(cell) = buffer.cell_mut((x, y)) {
+            cell.reset();
+            cell.set_style(style);
+        }
+    }
+}
+
+/// Draws a box with its border and title, like a bordered `Block` would. Only
+/// the cells inside of `buffer` are touched, so a box that's partly out of it
+/// keeps its shape.
+fn render_box(area: Rect, box_style: &BoxStyle, buffer: &mut Buffer, style: Style) {
+    if area.is_empty() {
+        return;
+    }
+    let (right, bottom) = (area.right() - 1, area.bottom() - 1);
+    let set = box_style.border.set();
+
+    for y in area.top()..area.bottom() {
+        clear_span(buffer, area.left(), right, y, style);
+        set_symbol(buffer, area.left(), y, set.vertical_left, style);
+        set_symbol(buffer, right, y, set.vertical_right, style);
+    }
+    for x in area.left()..area.right() {
+        set_symbol(buffer, x, area.top(), set.horizontal_top, style);
+        set_symbol(buffer, x, bottom, set.horizontal_bottom, style);
+    }
+    set_symbol(buffer, area.left(), area.top(), set.top_left, style);
+    set_symbol(buffer, right, area.top(), set.top_right, style);
+    set_symbol(buffer, area.left(), bottom, set.bottom_left, style);
+    set_symbol(buffer, right, bottom, set.bottom_right, style);
+
+    // The title sits between the corners, and is cut off on the side away
+    // from its alignment if it doesn't fit
+    let title_width = line_width(&box_style.title);
+    let width = area.width.saturating_sub(2);
+    let (indent, skip) = match box_style.title_alignment {
+        _ if title_width > width => (
+            0,
+            match box_style.title_alignment {
+                TextAlignment::Left => 0,
+                TextAlignment::Center => (title_width - width) / 2,
+                TextAlignment::Right => title_width - width,
+            },
+        ),
+        TextAlignment::Left => (0, 0),
+        TextAlignment::Center => ((width - title_width) / 2, 0),
+        TextAlignment::Right => (width - title_width, 0),
+    };
+    set_line(
+        buffer,
+        area.left() + 1 + indent,
+        area.top(),
+        width - indent,
+        &box_style.title,
+        skip,
+    );
+}
+
+/// Draws the lines of `content` into `area`, like a `Paragraph` would. Lines
+/// that don't fit are cut off at the end. Only the cells inside of `buffer`
+/// are touched.
+fn render_text(
+    area: Rect,
+    content: &str,
+    alignment: TextAlignment,
+    buffer: &mut Buffer,
+    style: Style,
+) {
+    buffer.set_style(area, style);
+    for (line, y) in content.lines().zip(area.top()..area.bottom()) {
+        let width = fitting_width(line, area.width);
+        let indent = match alignment {
+            TextAlignment::Left => 0,
+            TextAlignment::Center => (area.width / 2).saturating_sub(width / 2),
+            TextAlignment::Right => area.width.saturating_sub(width),
+        };
+        set_line(
+            buffer,
+            area.left() + indent,
+            y,
+            area.width - indent,
+            line,
+            0,
+        );
+    }
+}
+
+/// How many cells `text` takes up.
+fn line_width(text: &str) -> u16 {
+    u16::try_from(Line::from(text).width()).unwrap_or(u16::MAX)
+}
 
-        match el {
-            Self::Box { area } => {
-                Clear.render(*area, buffer);
-                Block::bordered().style(style).render(*area, buffer);
+/// How many cells the graphemes of `text` that fit into `width` take up.
+fn fitting_width(text: &str, width: u16) -> u16 {
+    let mut fitting: u16 = 0;
+    for grapheme in Line::from(text).styled_graphemes(Style::new()) {
+        let grapheme_width = line_width(grapheme.symbol);
+        if fitting.saturating_add(grapheme_width) > width {
+            break;
+        }
+        fitting += grapheme_width;
+    }
+    fitting
+}
+
+/// Writes `text` from column `x` of row `y`, leaving out its first `skip`
+/// cells and whatever doesn't fit into `width` cells. The cells keep their
+/// style.
+fn set_line(buffer: &mut Buffer, x: u16, y: u16, width: u16, text: &str, skip: u16) {
+    let (mut column, end): (u16, _) = (0, skip.saturating_add(width));
+    for grapheme in Line::from(text).styled_graphemes(Style::new()) {
+        let grapheme_width = line_width(grapheme.symbol);
+        if grapheme_width == 0 {
+            continue;
+        }
+        if column.saturating_add(grapheme_width) > end {
+            break;
+        }
+        if column >= skip {
+            if let Some(cell) = buffer.cell_mut((x.saturating_add(column - skip), y)) {
+                cell.set_symbol(grapheme.symbol);
+            }
+        }
+        column += grapheme_width;
+    }
+}
+
+/// Draws an ellipse inscribed in `area`. The outline steps outwards with
+/// rounded corners wherever it gets wider from one row to the next.
+fn render_ellipse(area: Rect, buffer: &mut Buffer, style: Style) {
+    let (width, height) = (area.width, area.height);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let half_width = (width - 1) as f32 / 2.;
+    let half_height = height as f32 / 2.;
+
+    // How far the outline is inset from the left (and right) edge on each row
+    let mut insets: Vec<u16> = (0..height)
+        .map(|y| {
+            let t = (half_height - (y as f32 + 0.5)).abs() / half_height;
+            (half_width * (1. - (1. - t * t).sqrt())).round() as u16
+        })
+        .collect();
+
+    // A middle row can't step outwards and back inwards at the same time
+    if height % 2 == 1 && height >= 3 {
+        insets[height as usize / 2] = insets[height as usize / 2 - 1];
+    }
+
+    let mirror = |x: u16| area.x + width - 1 - x;
+
+    for y in 0..height {
+        let inset = insets[y as usize];
+        let row = area.y + y;
+        clear_span(buffer, area.x + inset, mirror(inset), row, style);
+
+        // The rows a step is drawn between, and the glyphs at either end
+        let step = if y == 0 {
+            Some((inset, width - 1 - inset, "╭", "╮"))
+        } else if y == height - 1 {
+            Some((inset, width - 1 - inset, "╰", "╯"))
+        } else if y < height / 2 {
+            let prev = insets[y as usize - 1];
+            (inset < prev).then_some((inset, prev, "╭", "╯"))
+        } else {
+            let next = insets[y as usize + 1];
+            (inset < next).then_some((inset, next, "╰", "╮"))
+        };
+
+        match step {
+            Some((start, end, start_symbol, end_symbol)) if y == 0 || y == height - 1 => {
+                for x in start + 1..end {
+                    set_symbol(buffer, area.x + x, row, "─", style);
+                }
+                set_symbol(buffer, area.x + start, row, start_symbol, style);
+                set_symbol(buffer, area.x + end, row, end_symbol, style);
             }
-            Self::Text { area, content } => {
-                Paragraph::new(content.as_str())
-                    .style(style)
-                    .render(*area, buffer);
+            Some((start, end, start_symbol, end_symbol)) => {
+                let mirrored = |symbol| match symbol {
+                    "╭" => "╮",
+                    "╮" => "╭",
+                    "╰" => "╯",
+                    _ => "╰",
+                };
+                for x in start + 1..end {
+                    set_symbol(buffer, area.x + x, row, "─", style);
+                    set_symbol(buffer, mirror(x), row, "─", style);
+                }
+                set_symbol(buffer, area.x + start, row, start_symbol, style);
+                set_symbol(buffer, area.x + end, row, end_symbol, style);
+                set_symbol(buffer, mirror(start), row, mirrored(start_symbol), style);
+                set_symbol(buffer, mirror(end), row, mirrored(end_symbol), style);
             }
-            Self::Line(line) => {
-                line.render_to(buffer, style);
+            None => {
+                set_symbol(buffer, area.x + inset, row, "│", style);
+                set_symbol(buffer, mirror(inset), row, "│", style);
+            }
+        }
+    }
+}
+
+/// Draws a diamond inscribed in `area`, with its corners in the middle of
+/// each side. Where the edges are flatter than one column per row, the gaps
+/// are bridged with underscores.
+fn render_diamond(area: Rect, buffer: &mut Buffer, style: Style) {
+    let (width, height) = (area.width, area.height);
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    // Rows from the top corner down to the widest row
+    let rows = height.div_ceil(2);
+    let slope = if rows > 1 {
+        (width.saturating_sub(2) / 2) as f32 / (rows - 1) as f32
+    } else {
+        0.
+    };
+    let insets: Vec<u16> = (0..rows)
+        .map(|y| ((rows - 1 - y) as f32 * slope).round() as u16)
+        .collect();
+
+    let mirror = |x: u16| area.x + width - 1 - x;
+
+    for y in 0..height {
+        let row = area.y + y;
+        let middle = height % 2 == 1 && y == rows - 1;
+        let upper = y < rows && !middle;
+        let i = if y < rows { y } else { height - 1 - y } as usize;
+        let inset = insets[i];
+
+        clear_span(buffer, area.x + inset, mirror(inset), row, style);
+
+        // Upper rows bridge to the narrower row above on their outside,
+        // lower rows bridge to the narrower row below on their inside
+        let bridge = if upper {
+            insets
+                .get(i + 1)
+                .map(|&wider| (wider + 1, inset.saturating_sub(1)))
+        } else {
+            i.checked_sub(1)
+                .map(|narrower| (inset + 1, insets[narrower].saturating_sub(1)))
+        };
+        // With an odd width, the corners at the top and bottom are flat
+        if y == 0 || y == height - 1 {
+            let flat = if y == 0 { "‾" } else { "_" };
+            for x in inset + 1..width - 1 - inset {
+                set_symbol(buffer, area.x + x, row, flat, style);
+            }
+        }
+        if let Some((start, end)) = bridge {
+            for x in start..=end {
+                set_symbol(buffer, area.x + x, row, "_", style);
+                set_symbol(buffer, mirror(x), row, "_", style);
             }
         }
+
+        let (left, right) = if middle {
+            ("<", ">")
+        } else if upper {
+            ("╱", "╲")
+        } else {
+            ("╲", "╱")
+        };
+        set_symbol(buffer, area.x + inset, row, left, style);
+        set_symbol(buffer, mirror(inset), row, right, style);
+    }
+}
+
+/// Maps a cell inside of `from` to the corresponding cell inside of `to`.
+fn scale_position(position: Point, from: Area, to: Area) -> Point {
+    let scale = |value: i32, from_start: i32, from_len: u16, to_start: i32, to_len: u16| {
+        let (from_len, to_len) = (from_len.max(1) as i64 - 1, to_len.max(1) as i64 - 1);
+        let offset = (value - from_start).max(0) as i64;
+        (offset * to_len + from_len / 2)
+            .checked_div(from_len)
+            .map_or(to_start, |scaled| to_start + scaled as i32)
+    };
+
+    Point {
+        x: scale(position.x, from.x, from.width, to.x, to.width),
+        y: scale(position.y, from.y, from.height, to.y, to.height),
+    }
+}
+
+pub(super) fn scale_rect(area: Area, from: Area, to: Area) -> Area {
+    let top_left = scale_position(area.as_point(), from, to);
+    let bottom_right = scale_position(Point::new(area.right() - 1, area.bottom() - 1), from, to);
+
+    Area {
+        x: top_left.x,
+        y: top_left.y,
+        width: (bottom_right.x - top_left.x + 1).max(0) as u16,
+        height: (bottom_right.y - top_left.y + 1).max(0) as u16,
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct StraightLine {
-    pub from: Position,
-    pub to: Position,
+    pub from: Point,
+    pub to: Point,
     pub direction: LineDirection,
+    #[serde(default)]
+    pub style: LineStyle,
+    #[serde(default)]
+    pub start: Arrowhead,
+    #[serde(default)]
+    pub end: Arrowhead,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum LineStyle {
+    #[default]
+    Thin,
+    Thick,
+    Double,
+    Dashed,
+    Ascii,
+}
+
+impl LineStyle {
+    fn horizontal(self) -> &'static str {
+        match self {
+            Self::Thin => "─",
+            Self::Thick => "━",
+            Self::Double => "═",
+            Self::Dashed => "┄",
+            Self::Ascii => "-",
+        }
+    }
+
+    fn vertical(self) -> &'static str {
+        match self {
+            Self::Thin => "│",
+            Self::Thick => "┃",
+            Self::Double => "║",
+            Self::Dashed => "┆",
+            Self::Ascii => "|",
+        }
+    }
+
+    /// The corner joining a line going up or down with one going left or
+    /// right.
+    pub(crate) fn corner(self, up: bool, left: bool) -> &'static str {
+        let corners = match self {
+            Self::Thin | Self::Dashed => ["┘", "└", "┐", "┌"],
+            Self::Thick => ["┛", "┗", "┓", "┏"],
+            Self::Double => ["╝", "╚", "╗", "╔"],
+            Self::Ascii => ["+"; 4],
+        };
+        match (up, left) {
+            (true, true) => corners[0],
+            (true, false) => corners[1],
+            (false, true) => corners[2],
+            (false, false) => corners[3],
+        }
+    }
+
+    /// There are no thick, double or dashed diagonals, so those stay thin.
+    fn diagonal(self, down_right: bool) -> &'static str {
+        match (self, down_right) {
+            (Self::Ascii, true) => "\\",
+            (Self::Ascii, false) => "/",
+            (_, true) => "＼",
+            (_, false) => "／",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Display, EnumIter)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "lowercase")]
+pub enum Arrowhead {
+    #[default]
+    None,
+    Arrow,
+    Triangle,
+    Chevron,
+}
+
+impl Arrowhead {
+    /// The glyph of an arrowhead pointing towards `direction`.
+    fn symbol(self, direction: &LineDirection) -> Option<&'static str> {
+        use LineDirection::*;
+
+        let symbols = match self {
+            Self::None => return None,
+            Self::Arrow => ["↑", "↗", "→", "↘", "↓", "↙", "←", "↖"],
+            Self::Triangle => ["▲", "◥", "▶", "◢", "▼", "◣", "◀", "◤"],
+            Self::Chevron => ["^", ">", ">", ">", "v", "<", "<", "<"],
+        };
+        Some(match direction {
+            Up => symbols[0],
+            UpRight => symbols[1],
+            Right => symbols[2],
+            DownRight => symbols[3],
+            Down => symbols[4],
+            DownLeft => symbols[5],
+            Left => symbols[6],
+            UpLeft => symbols[7],
+        })
+    }
 }
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LineDirection {
     Up,
     UpRight,
@@ -83,8 +961,23 @@ pub enum LineDirection {
     UpLeft,
 }
 
+impl LineDirection {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::UpRight => Self::DownLeft,
+            Self::Right => Self::Left,
+            Self::DownRight => Self::UpLeft,
+            Self::Down => Self::Up,
+            Self::DownLeft => Self::UpRight,
+            Self::Left => Self::Right,
+            Self::UpLeft => Self::DownRight,
+        }
+    }
+}
+
 impl StraightLine {
-    pub fn new(from: Position, to: Position) -> Option<Self> {
+    pub fn new(from: Point, to: Point) -> Option<Self> {
         let (x, y) = (to.x as f32 - from.x as f32, to.y as f32 - from.y as f32);
 
         let diamond_angle = if y >= 0. {
@@ -100,94 +993,70 @@ impl StraightLine {
         };
 
         match diamond_angle % 4.0 {
-            ..0.25 | 3.75.. => Some(Self {
-                from: (from.x, from.y).into(),
-                to: (to.x, from.y).into(),
-                direction: LineDirection::Right,
-            }),
-            0.25..0.75 => Some(Self {
-                from: (from.x, from.y).into(),
-                to: (from.x + (to.y - from.y) * 2, to.y).into(),
-                direction: LineDirection::DownRight,
-            }),
-            0.75..1.25 => Some(Self {
-                from: (from.x, from.y).into(),
-                to: (from.x, to.y).into(),
-                direction: LineDirection::Down,
-            }),
-            1.25..1.75 => {
-                let d = to.y.saturating_sub(from.y).min(from.x / 2);
-                Some(Self {
-                    from: (from.x, from.y).into(),
-                    to: (from.x - d * 2, from.y + d).into(),
-                    direction: LineDirection::DownLeft,
-                })
-            }
-            1.75..2.25 => Some(Self {
-                from: (from.x, from.y).into(),
-                to: (to.x, from.y).into(),
-                direction: LineDirection::Left,
-            }),
-            2.25..2.75 => {
-                let d = from.y.saturating_sub(to.y).min(from.x / 2);
-                Some(Self {
-                    from: (from.x, from.y).into(),
-                    to: (from.x - d * 2, from.y - d).into(),
-                    direction: LineDirection::UpLeft,
-                })
-            }
-            2.75..3.25 => Some(Self {
-                from: (from.x, from.y).into(),
-                to: (from.x, to.y).into(),
-                direction: LineDirection::Up,
-            }),
-            3.25..3.75 => Some(Self {
-                from: (from.x, from.y).into(),
-                to: (from.x + (from.y - to.y) * 2, to.y).into(),
-                direction: LineDirection::UpRight,
-            }),
+            ..0.25 | 3.75.. => Some(((to.x, from.y).into(), LineDirection::Right)),
+            0.25..0.75 => Some((
+                (from.x + (to.y - from.y) * 2, to.y).into(),
+                LineDirection::DownRight,
+            )),
+            0.75..1.25 => Some(((from.x, to.y).into(), LineDirection::Down)),
+            1.25..1.75 => Some((
+                (from.x - (to.y - from.y) * 2, to.y).into(),
+                LineDirection::DownLeft,
+            )),
+            1.75..2.25 => Some(((to.x, from.y).into(), LineDirection::Left)),
+            2.25..2.75 => Some((
+                (from.x - (from.y - to.y) * 2, to.y).into(),
+                LineDirection::UpLeft,
+            )),
+            2.75..3.25 => Some(((from.x, to.y).into(), LineDirection::Up)),
+            3.25..3.75 => Some((
+                (from.x + (from.y - to.y) * 2, to.y).into(),
+                LineDirection::UpRight,
+            )),
             _ => None,
         }
+        .map(|(to, direction): (Point, _)| Self {
+            from,
+            to,
+            direction,
+            style: LineStyle::default(),
+            start: Arrowhead::None,
+            end: Arrowhead::None,
+        })
     }
-    pub fn offset(&self, offset: Offset) -> StraightLine {
-        StraightLine {
-            from: Position {
-                x: self.from.x.saturating_add_signed(offset.x as i16),
-                y: self.from.y.saturating_add_signed(offset.y as i16),
-            },
-            to: Position {
-                x: self.to.x.saturating_add_signed(offset.x as i16),
-                y: self.to.y.saturating_add_signed(offset.y as i16),
-            },
-            direction: self.direction.clone(),
-        }
-    }
-    pub fn area(&self) -> Rect {
-        let (min_x, max_x) = if self.from.x < self.to.x {
-            (self.from.x, self.to.x)
-        } else {
-            (self.to.x, self.from.x)
-        };
 
-        let (min_y, max_y) = if self.from.y < self.to.y {
-            (self.from.y, self.to.y)
+    /// Gives this line the style and arrowheads of `other`. If `reversed`, the
+    /// line runs the other way around, so the arrowheads swap ends.
+    pub fn styled_like(self, other: &StraightLine, reversed: bool) -> Self {
+        let (start, end) = if reversed {
+            (other.end, other.start)
         } else {
-            (self.to.y, self.from.y)
+            (other.start, other.end)
         };
+        Self {
+            style: other.style,
+            start,
+            end,
+            ..self
+        }
+    }
 
-        Rect {
-            x: min_x,
-            y: min_y,
-            width: max_x - min_x + 1,
-            height: max_y - min_y + 1,
+    pub fn offset(&self, offset: Offset) -> StraightLine {
+        StraightLine {
+            from: self.from.offset(offset),
+            to: self.to.offset(offset),
+            ..self.clone()
         }
     }
-    pub fn render_to(&self, buffer: &mut ratatui::prelude::Buffer, style: Style) {
+    pub fn area(&self) -> Area {
+        Area::between(self.from, self.to)
+    }
+    pub fn render_to(&self, buffer: &mut ratatui::prelude::Buffer, origin: Point, style: Style) {
         match self.direction {
             LineDirection::Right => {
                 for x in self.from.x..=self.to.x {
-                    if let Some(cell) = buffer.cell_mut(Position::new(x, self.from.y)) {
-                        cell.set_char('─');
+                    if let Some(cell) = cell_at(buffer, origin, Point::new(x, self.from.y)) {
+                        cell.set_symbol(self.style.horizontal());
                         cell.set_style(style);
                     }
                 }
@@ -196,18 +1065,18 @@ impl StraightLine {
                 for pos in (self.from.x..=self.to.x)
                     .step_by(2)
                     .zip(self.from.y..=self.to.y)
-                    .map(Position::from)
+                    .map(Point::from)
                 {
-                    if let Some(cell) = buffer.cell_mut(pos) {
-                        cell.set_symbol("＼");
+                    if let Some(cell) = cell_at(buffer, origin, pos) {
+                        cell.set_symbol(self.style.diagonal(true));
                         cell.set_style(style);
                     }
                 }
             }
             LineDirection::Down => {
                 for y in self.from.y..=self.to.y {
-                    if let Some(cell) = buffer.cell_mut(Position::new(self.from.x, y)) {
-                        cell.set_char('│');
+                    if let Some(cell) = cell_at(buffer, origin, Point::new(self.from.x, y)) {
+                        cell.set_symbol(self.style.vertical());
                         cell.set_style(style);
                     }
                 }
@@ -216,18 +1085,18 @@ impl StraightLine {
                 for pos in (self.to.x..=self.from.x)
                     .step_by(2)
                     .zip((self.from.y..=self.to.y).rev())
-                    .map(Position::from)
+                    .map(Point::from)
                 {
-                    if let Some(cell) = buffer.cell_mut(pos) {
-                        cell.set_symbol("／");
+                    if let Some(cell) = cell_at(buffer, origin, pos) {
+                        cell.set_symbol(self.style.diagonal(false));
                         cell.set_style(style);
                     }
                 }
             }
             LineDirection::Left => {
                 for x in self.to.x..=self.from.x {
-                    if let Some(cell) = buffer.cell_mut(Position::from((x, self.from.y))) {
-                        cell.set_char('─');
+                    if let Some(cell) = cell_at(buffer, origin, Point::new(x, self.from.y)) {
+                        cell.set_symbol(self.style.horizontal());
                         cell.set_style(style);
                     }
                 }
@@ -236,18 +1105,18 @@ impl StraightLine {
                 for pos in (self.to.x..=self.from.x)
                     .step_by(2)
                     .zip(self.to.y..=self.from.y)
-                    .map(Position::from)
+                    .map(Point::from)
                 {
-                    if let Some(cell) = buffer.cell_mut(pos) {
-                        cell.set_symbol("＼");
+                    if let Some(cell) = cell_at(buffer, origin, pos) {
+                        cell.set_symbol(self.style.diagonal(true));
                         cell.set_style(style);
                     }
                 }
             }
             LineDirection::Up => {
                 for y in self.to.y..=self.from.y {
-                    if let Some(cell) = buffer.cell_mut(Position::new(self.from.x, y)) {
-                        cell.set_char('│');
+                    if let Some(cell) = cell_at(buffer, origin, Point::new(self.from.x, y)) {
+                        cell.set_symbol(self.style.vertical());
                         cell.set_style(style);
                     }
                 }
@@ -256,14 +1125,260 @@ impl StraightLine {
                 for pos in (self.from.x..=self.to.x)
                     .step_by(2)
                     .zip((self.to.y..=self.from.y).rev())
-                    .map(Position::from)
+                    .map(Point::from)
                 {
-                    if let Some(cell) = buffer.cell_mut(pos) {
-                        cell.set_symbol("／");
+                    if let Some(cell) = cell_at(buffer, origin, pos) {
+                        cell.set_symbol(self.style.diagonal(false));
                         cell.set_style(style);
                     }
                 }
             }
         }
+
+        for (arrowhead, position, direction) in [
+            (self.start, self.from, self.direction.opposite()),
+            (self.end, self.to, self.direction.clone()),
+        ] {
+            if let Some(symbol) = arrowhead.symbol(&direction) {
+                if let Some(cell) = cell_at(buffer, origin, position) {
+                    cell.set_symbol(symbol);
+                    cell.set_style(style);
+                }
+            }
+        }
+    }
+}
+
+/// A line through any number of vertices. Each stretch between two vertices
+/// runs in one of the eight directions a [`StraightLine`] can, or bends at a
+/// right angle if it can't.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    #[serde(default)]
+    pub style: LineStyle,
+    #[serde(default)]
+    pub start: Arrowhead,
+    #[serde(default)]
+    pub end: Arrowhead,
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Point>) -> Self {
+        Self {
+            points,
+            style: LineStyle::default(),
+            start: Arrowhead::None,
+            end: Arrowhead::None,
+        }
+    }
+
+    pub fn map(&self, f: impl Fn(Point) -> Point) -> Self {
+        Self {
+            points: self.points.iter().copied().map(f).collect(),
+            ..self.clone()
+        }
+    }
+
+    /// The vertices, with corners added where going straight from one to the
+    /// next isn't possible.
+    pub fn path(&self) -> Vec<Point> {
+        let mut path = self.points.first().copied().into_iter().collect::<Vec<_>>();
+        for (from, to) in self.points.iter().zip(self.points.iter().skip(1)) {
+            if StraightLine::new(*from, *to).is_none_or(|line| line.to != *to) {
+                path.push(Point::new(to.x, from.y));
+            }
+            path.push(*to);
+        }
+        path.dedup();
+        path
+    }
+
+    pub fn area(&self) -> Area {
+        self.points
+            .iter()
+            .map(|position| Area::new(position.x, position.y, 1, 1))
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default()
+    }
+
+    pub fn render_to(&self, buffer: &mut Buffer, origin: Point, style: Style) {
+        render_path(
+            &self.path(),
+            self.style,
+            self.start,
+            self.end,
+            buffer,
+            origin,
+            style,
+        );
+    }
+}
+
+/// Draws straight lines from one point of `path` to the next, with corners
+/// where they meet at a right angle. Where a diagonal meets a straight line,
+/// the diagonal keeps its end.
+pub(crate) fn render_path(
+    path: &[Point],
+    line_style: LineStyle,
+    start: Arrowhead,
+    end: Arrowhead,
+    buffer: &mut Buffer,
+    origin: Point,
+    style: Style,
+) {
+    let mut lines = path
+        .windows(2)
+        .filter_map(|ends| StraightLine::new(ends[0], ends[1]))
+        .map(|line| StraightLine {
+            style: line_style,
+            ..line
+        })
+        .collect::<Vec<_>>();
+    if let Some(first) = lines.first_mut() {
+        first.start = start;
+    }
+    if let Some(last) = lines.last_mut() {
+        last.end = end;
+    }
+
+    for line in lines.iter() {
+        line.render_to(buffer, origin, style);
+    }
+
+    for (incoming, outgoing) in lines.iter().zip(lines.iter().skip(1)) {
+        use LineDirection::*;
+
+        let vertical = |direction: &LineDirection| matches!(direction, Up | Down);
+        let horizontal = |direction: &LineDirection| matches!(direction, Left | Right);
+        let diagonal = |direction: &LineDirection| !vertical(direction) && !horizontal(direction);
+
+        let symbol = if vertical(&incoming.direction) && horizontal(&outgoing.direction)
+            || horizontal(&incoming.direction) && vertical(&outgoing.direction)
+        {
+            let up = incoming.direction == Down || outgoing.direction == Up;
+            let left = incoming.direction == Right || outgoing.direction == Left;
+            line_style.corner(up, left)
+        } else if diagonal(&incoming.direction) != diagonal(&outgoing.direction) {
+            // Whichever line was drawn last covered the end of the diagonal
+            let line = if diagonal(&incoming.direction) {
+                incoming
+            } else {
+                outgoing
+            };
+            line_style.diagonal(matches!(line.direction, DownRight | UpLeft))
+        } else {
+            continue;
+        };
+
+        if let Some(cell) = cell_at(buffer, origin, incoming.to) {
+            cell.set_symbol(symbol);
+            cell.set_style(style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing::buffer_to_string;
+
+    fn render(kind: ElementKind) -> String {
+        let area = kind.area();
+        let mut buffer = Buffer::empty(Rect::new(0, 0, area.width, area.height));
+        kind.render_to(&mut buffer, area.as_point(), false);
+        buffer_to_string(&buffer)
+    }
+
+    #[test]
+    fn test_ellipse() {
+        assert_eq!(
+            render(ElementKind::Ellipse {
+                area: Area::new(0, 0, 10, 5)
+            }),
+            "  ╭────╮  \n╭─╯    ╰─╮\n│        │\n╰─╮    ╭─╯\n  ╰────╯  \n"
+        );
+    }
+
+    #[test]
+    fn test_diamond() {
+        assert_eq!(
+            render(ElementKind::Diamond {
+                area: Area::new(0, 0, 6, 4)
+            }),
+            " _╱╲_ \n╱    ╲\n╲_  _╱\n  ╲╱  \n"
+        );
+    }
+
+    #[test]
+    fn test_paint_and_erase() {
+        let mut paint = ElementKind::Paint { pixels: vec![] };
+        paint.paint(Point::new(1, 0), '#');
+        paint.paint(Point::new(2, 1), '#');
+        paint.paint(Point::new(1, 0), '*');
+        assert_eq!(render(paint.clone()), "* \n #\n");
+
+        assert!(paint.erase(Point::new(2, 1)));
+        assert!(!paint.erase(Point::new(2, 1)));
+        assert_eq!(paint.area(), Area::new(1, 0, 1, 1));
+    }
+
+    #[test]
+    fn test_line_style_and_arrowheads() {
+        let mut line = StraightLine::new(Point::new(0, 0), Point::new(4, 0)).unwrap();
+        line.style = LineStyle::Double;
+        line.end = Arrowhead::Triangle;
+        assert_eq!(render(ElementKind::Line(line.clone())), "════▶\n");
+
+        // Dragging the first handle flips the line, but not its arrowheads
+        let moved = StraightLine::new(line.to, Point::new(1, 0))
+            .unwrap()
+            .styled_like(&line, true);
+        assert_eq!(render(ElementKind::Line(moved)), "═══▶\n");
+    }
+
+    #[test]
+    fn test_polyline() {
+        let polyline = Polyline::new(vec![
+            Point::new(0, 0),
+            Point::new(4, 0),
+            Point::new(8, 2),
+            Point::new(6, 4),
+        ]);
+        assert_eq!(
+            polyline.path(),
+            [
+                Point::new(0, 0),
+                Point::new(4, 0),
+                Point::new(8, 2),
+                Point::new(6, 2),
+                Point::new(6, 4),
+            ]
+        );
+        assert_eq!(
+            render(ElementKind::Polyline(polyline)),
+            "────＼    \n      ＼  \n      ┌─＼\n      │  \n      │  \n"
+        );
+    }
+
+    #[test]
+    fn test_box_style() {
+        let kind = ElementKind::Box {
+            area: Area::new(0, 0, 7, 3),
+            style: BoxStyle {
+                border: BorderStyle::Ascii,
+                title: "DB".into(),
+                title_alignment: TextAlignment::Center,
+                shadow: Shadow::Medium,
+            },
+        };
+        assert_eq!(kind.drawn_area(), Area::new(0, 0, 9, 4));
+
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 9, 4));
+        kind.render_to(&mut buffer, Point::ORIGIN, false);
+        assert_eq!(
+            buffer_to_string(&buffer),
+            "+-DB--+  \n|     |▒▒\n+-----+▒▒\n  ▒▒▒▒▒▒▒\n"
+        );
     }
 }
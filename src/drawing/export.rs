@@ -0,0 +1,579 @@
+use std::fmt::Write;
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier, Style},
+};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString};
+
+use super::junction;
+use crate::app::color_scheme;
+
+/// What plaintext exports are wrapped in, so they can be pasted into a
+/// Markdown file or a code comment as they are.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    Display,
+    EnumIter,
+    EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Wrap {
+    #[default]
+    #[strum(to_string = "none")]
+    None,
+    /// A ```` ```text ```` fence.
+    #[strum(to_string = "markdown", serialize = "md")]
+    Markdown,
+    #[strum(to_string = "//", serialize = "slashes")]
+    Slashes,
+    #[strum(to_string = "#", serialize = "hashes")]
+    Hashes,
+    #[strum(to_string = "--", serialize = "dashes")]
+    Dashes,
+    /// A `/* */` comment, with a ` * ` in front of every line.
+    #[strum(to_string = "/*", serialize = "block")]
+    Block,
+    #[strum(to_string = ";;", serialize = "semicolons")]
+    Semicolons,
+}
+
+/// How plaintext exports are laid out.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct TextOptions {
+    pub wrap: Wrap,
+    pub trim_trailing_whitespace: bool,
+    /// How many spaces go in front of every line, after the comment prefix.
+    pub padding: u16,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        Self {
+            wrap: Wrap::None,
+            trim_trailing_whitespace: true,
+            padding: 0,
+        }
+    }
+}
+
+/// Wraps the lines of a plaintext export as set in `options`.
+pub fn wrap_text(text: &str, options: &TextOptions) -> String {
+    let (open, prefix, close) = match options.wrap {
+        Wrap::None => (None, "", None),
+        Wrap::Markdown => (Some("```text"), "", Some("```")),
+        Wrap::Slashes => (None, "// ", None),
+        Wrap::Hashes => (None, "# ", None),
+        Wrap::Dashes => (None, "-- ", None),
+        Wrap::Block => (Some("/*"), " * ", Some(" */")),
+        Wrap::Semicolons => (None, ";; ", None),
+    };
+    let padding = " ".repeat(options.padding as usize);
+
+    let mut out = String::with_capacity(text.len());
+    for line in open
+        .map(str::to_string)
+        .into_iter()
+        .chain(text.lines().map(|line| format!("{prefix}{padding}{line}")))
+        .chain(close.map(str::to_string))
+    {
+        out.push_str(if options.trim_trailing_whitespace {
+            line.trim_end()
+        } else {
+            &line
+        });
+        out.push('\n');
+    }
+    out
+}
+
+/// How `:export-svg` lays out the cells.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SvgOptions {
+    pub font_family: String,
+    /// In pixels.
+    pub font_size: f32,
+    /// How wide a cell is compared to how tall it is.
+    pub cell_aspect: f32,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            font_family: "ui-monospace, Menlo, Consolas, monospace".into(),
+            font_size: 14.0,
+            cell_aspect: 0.5,
+        }
+    }
+}
+
+/// Like [`super::buffer_to_string`], but with every glyph swapped for the
+/// closest one in 7-bit ASCII, for code comments, RFC-style docs and
+/// terminals that don't do Unicode.
+pub fn buffer_to_ascii(buffer: &Buffer) -> String {
+    let area = buffer.area();
+    let mut out_string = String::with_capacity(area.area() as usize);
+
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            out_string.push(buffer.cell((x, y)).map_or(' ', |c| ascii(c.symbol())));
+        }
+        out_string.push('\n');
+    }
+
+    out_string
+}
+
+/// Draws every cell of `buffer` as monospace text in an SVG, keeping its
+/// colours. Each glyph is placed in its cell explicitly, so the grid holds up
+/// no matter how wide the font actually is.
+pub fn buffer_to_svg(buffer: &Buffer, options: &SvgOptions) -> String {
+    let area = buffer.area();
+    let cell_height = options.font_size * 1.2;
+    let cell_width = cell_height * options.cell_aspect;
+    let (width, height) = (
+        area.width as f32 * cell_width,
+        area.height as f32 * cell_height,
+    );
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="{}" font-size="{}">"#,
+        escape(&options.font_family),
+        px(options.font_size),
+        w = px(width),
+        h = px(height),
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect width="100%" height="100%" fill="{}"/>"#,
+        hex(color_scheme::BG_BASE).unwrap_or_default()
+    );
+
+    for y in area.top()..area.bottom() {
+        let top = (y - area.y) as f32 * cell_height;
+        let cells = (area.left()..area.right())
+            .filter_map(|x| Some((x, buffer.cell((x, y))?)))
+            .collect::<Vec<_>>();
+
+        for (x, cell) in &cells {
+            if let Some(fill) = hex(cell.bg) {
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{fill}"/>"#,
+                    px((x - area.x) as f32 * cell_width),
+                    px(top),
+                    px(cell_width),
+                    px(cell_height),
+                );
+            }
+        }
+
+        // Neighbouring glyphs that look the same go into one text element
+        let glyphs = cells
+            .iter()
+            .filter(|(_, cell)| !cell.symbol().trim().is_empty())
+            .collect::<Vec<_>>();
+        for run in glyphs.chunk_by(|(a, first), (b, second)| {
+            a + 1 == *b && first.fg == second.fg && first.modifier == second.modifier
+        }) {
+            let (_, cell) = run[0];
+            let positions = run
+                .iter()
+                .map(|(x, _)| px((x - area.x) as f32 * cell_width + cell_width / 2.0))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let text = run
+                .iter()
+                .map(|(_, cell)| escape(cell.symbol()))
+                .collect::<String>();
+            let _ = writeln!(
+                svg,
+                r#"<text x="{positions}" y="{}" fill="{}"{} text-anchor="middle" dominant-baseline="central" xml:space="preserve">{text}</text>"#,
+                px(top + cell_height / 2.0),
+                hex(cell.fg).unwrap_or_else(|| hex(color_scheme::FG_BASE).unwrap_or_default()),
+                svg_modifiers(cell),
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// The drawing as a `<pre>` block to paste into a web page, with the colours
+/// and modifiers of every cell as inline styles.
+pub fn buffer_to_html(buffer: &Buffer) -> String {
+    let area = buffer.area();
+    let mut html = format!(
+        r#"<pre style="font-family: monospace; line-height: 1.2; padding: 1em; color: {}; background: {}">"#,
+        hex(color_scheme::FG_BASE).unwrap_or_default(),
+        hex(color_scheme::BG_BASE).unwrap_or_default(),
+    );
+    html.push('\n');
+
+    for y in area.top()..area.bottom() {
+        for (style, text) in styled_runs(buffer, y) {
+            let css = css(style);
+            if css.is_empty() {
+                html.push_str(&escape(&text));
+            } else {
+                let _ = write!(html, r#"<span style="{css}">{}</span>"#, escape(&text));
+            }
+        }
+        html.push('\n');
+    }
+
+    html.push_str("</pre>\n");
+    html
+}
+
+/// The drawing as text with SGR escape codes for the colours and modifiers
+/// of every cell, for terminals and `cat`.
+pub fn buffer_to_ansi(buffer: &Buffer) -> String {
+    let area = buffer.area();
+    let mut ansi = String::new();
+
+    for y in area.top()..area.bottom() {
+        for (style, text) in styled_runs(buffer, y) {
+            ansi.push_str(&sgr(style));
+            ansi.push_str(&text);
+        }
+        ansi.push_str("\x1b[0m\n");
+    }
+
+    ansi
+}
+
+/// The cells of row `y`, split up wherever their style changes.
+fn styled_runs(buffer: &Buffer, y: u16) -> Vec<(Style, String)> {
+    let area = buffer.area();
+    let mut runs: Vec<(Style, String)> = vec![];
+
+    for cell in (area.left()..area.right()).filter_map(|x| buffer.cell((x, y))) {
+        let style = cell.style();
+        match runs.last_mut() {
+            Some((last, text)) if *last == style => text.push_str(cell.symbol()),
+            _ => runs.push((style, cell.symbol().to_string())),
+        }
+    }
+
+    runs
+}
+
+fn css(style: Style) -> String {
+    let mut properties = vec![];
+    if let Some(color) = style.fg.and_then(hex) {
+        properties.push(format!("color: {color}"));
+    }
+    if let Some(color) = style.bg.and_then(hex) {
+        properties.push(format!("background: {color}"));
+    }
+    if style.add_modifier.contains(Modifier::BOLD) {
+        properties.push("font-weight: bold".into());
+    }
+    if style.add_modifier.contains(Modifier::ITALIC) {
+        properties.push("font-style: italic".into());
+    }
+    if style.add_modifier.contains(Modifier::UNDERLINED) {
+        properties.push("text-decoration: underline".into());
+    }
+    properties.join("; ")
+}
+
+/// An escape code that resets the style and then sets it to `style`.
+fn sgr(style: Style) -> String {
+    let mut codes = vec!["0".to_string()];
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if style.add_modifier.contains(modifier) {
+            codes.push(code.into());
+        }
+    }
+    codes.extend(style.fg.and_then(|color| sgr_color(color, 30)));
+    codes.extend(style.bg.and_then(|color| sgr_color(color, 40)));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+/// The parameters for `color`, where `base` is 30 for the foreground and 40
+/// for the background.
+fn sgr_color(color: Color, base: u8) -> Option<String> {
+    Some(match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", base + 8),
+        Color::Indexed(index) => format!("{};5;{index}", base + 8),
+        named => match ansi_index(named)? {
+            index @ 0..8 => (base + index).to_string(),
+            index => (base + 60 + index - 8).to_string(),
+        },
+    })
+}
+
+fn svg_modifiers(cell: &Cell) -> String {
+    let mut attributes = String::new();
+    if cell.modifier.contains(Modifier::BOLD) {
+        attributes.push_str(r#" font-weight="bold""#);
+    }
+    if cell.modifier.contains(Modifier::ITALIC) {
+        attributes.push_str(r#" font-style="italic""#);
+    }
+    if cell.modifier.contains(Modifier::UNDERLINED) {
+        attributes.push_str(r#" text-decoration="underline""#);
+    }
+    attributes
+}
+
+/// Rounds to two decimals, without trailing zeros.
+fn px(value: f32) -> String {
+    ((value * 100.0).round() / 100.0).to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The colour as `#rrggbb`, or `None` for the terminal's default. Named and
+/// indexed colours use the xterm palette.
+fn hex(color: Color) -> Option<String> {
+    let (r, g, b) = rgb(color)?;
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    const ANSI: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+
+    let index = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => return Some((r, g, b)),
+        Color::Indexed(index) => index,
+        named => ansi_index(named)?,
+    };
+
+    Some(match index {
+        0..16 => ANSI[index as usize],
+        // A 6×6×6 colour cube
+        16..232 => {
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        // And 24 shades of grey
+        _ => {
+            let level = 8 + (index - 232) * 10;
+            (level, level, level)
+        }
+    })
+}
+
+/// Where a named colour is in the 16 colour palette.
+fn ansi_index(color: Color) -> Option<u8> {
+    Some(match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        _ => return None,
+    })
+}
+
+/// The ASCII stand-in for `symbol`. Lines become `-` and `|`, and wherever
+/// they turn or meet, there's a `+`.
+fn ascii(symbol: &str) -> char {
+    let Some(c) = symbol.chars().next() else {
+        // The cell after a wide glyph
+        return ' ';
+    };
+    if c.is_ascii() {
+        return c;
+    }
+    if let Some(c) = junction::ascii(symbol) {
+        return c;
+    }
+
+    match c {
+        '┄' | '‾' => '-',
+        '┆' => '|',
+        '╱' | '／' => '/',
+        '╲' | '＼' => '\\',
+        '↑' | '▲' => '^',
+        '↓' | '▼' => 'v',
+        '→' | '↗' | '↘' | '▶' | '◥' | '◢' => '>',
+        '←' | '↙' | '↖' | '◀' | '◣' | '◤' => '<',
+        '░' | '▒' | '▓' | '█' | '■' | '▄' | '▀' => '#',
+        '·' => '.',
+        _ => '?',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use ratatui::layout::Rect;
+
+    use super::*;
+    use crate::drawing::{
+        Area, Arrowhead, BorderStyle, BoxStyle, DrawingCanvas, ElementKind, Point, Shadow,
+        StraightLine,
+    };
+
+    #[test]
+    fn test_svg() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buffer.set_string(0, 0, "<>", Style::new().fg(Color::Rgb(255, 0, 0)));
+        buffer.set_string(2, 0, "x", Style::new().bg(Color::Indexed(21)));
+        let options = SvgOptions {
+            font_family: "Iosevka".into(),
+            font_size: 10.0,
+            cell_aspect: 0.5,
+        };
+
+        assert_eq!(
+            buffer_to_svg(&buffer, &options),
+            concat!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="18" height="12" viewBox="0 0 18 12" font-family="Iosevka" font-size="10">"#,
+                "\n",
+                r##"<rect width="100%" height="100%" fill="#09090b"/>"##,
+                "\n",
+                r##"<rect x="12" y="0" width="6" height="12" fill="#0000ff"/>"##,
+                "\n",
+                r##"<text x="3 9" y="6" fill="#ff0000" text-anchor="middle" dominant-baseline="central" xml:space="preserve">&lt;&gt;</text>"##,
+                "\n",
+                r##"<text x="15" y="6" fill="#e4e4e7" text-anchor="middle" dominant-baseline="central" xml:space="preserve">x</text>"##,
+                "\n",
+                "</svg>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_html_and_ansi() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_string(
+            0,
+            0,
+            "a<",
+            Style::new()
+                .fg(Color::LightRed)
+                .add_modifier(Modifier::BOLD),
+        );
+        buffer.set_string(1, 1, "b", Style::new().bg(Color::Rgb(1, 2, 3)));
+
+        assert_eq!(
+            buffer_to_html(&buffer),
+            concat!(
+                r##"<pre style="font-family: monospace; line-height: 1.2; padding: 1em; color: #e4e4e7; background: #09090b">"##,
+                "\n",
+                r##"<span style="color: #ff0000; font-weight: bold">a&lt;</span>  "##,
+                "\n",
+                r##" <span style="background: #010203">b</span>  "##,
+                "\n",
+                "</pre>\n",
+            )
+        );
+        assert_eq!(
+            buffer_to_ansi(&buffer),
+            concat!(
+                "\x1b[0;1;91ma<\x1b[0m  \x1b[0m\n",
+                "\x1b[0m \x1b[0;48;2;1;2;3mb\x1b[0m  \x1b[0m\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        let text = "┌─┐  \n└─┘  \n     \n";
+
+        let options = TextOptions {
+            wrap: Wrap::Block,
+            padding: 1,
+            ..Default::default()
+        };
+        assert_eq!(wrap_text(text, &options), "/*\n *  ┌─┐\n *  └─┘\n *\n */\n");
+
+        let options = TextOptions {
+            wrap: Wrap::Markdown,
+            trim_trailing_whitespace: false,
+            padding: 0,
+        };
+        assert_eq!(
+            wrap_text(text, &options),
+            "```text\n┌─┐  \n└─┘  \n     \n```\n"
+        );
+        assert_eq!("//".parse(), Ok(Wrap::Slashes));
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut canvas = DrawingCanvas::default();
+        canvas.push(ElementKind::Box {
+            area: Area::new(0, 0, 6, 3),
+            style: BoxStyle {
+                border: BorderStyle::Double,
+                shadow: Shadow::Light,
+                ..Default::default()
+            },
+        });
+        let mut line = StraightLine::new(Point::new(3, 1), Point::new(10, 1)).unwrap();
+        line.end = Arrowhead::Triangle;
+        canvas.push(ElementKind::Line(line));
+        canvas.push(ElementKind::Diamond {
+            area: Area::new(0, 4, 4, 2),
+        });
+        canvas.render(&Default::default(), &None, Area::new(0, 0, 11, 6));
+
+        assert_eq!(
+            buffer_to_ascii(&canvas.buffer),
+            "+----+     \n|  --+---->\n+----+##   \n  ######   \n/--\\       \n\\__/       \n"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use ratatui::layout::{Offset, Position, Rect};
+use serde::{Deserialize, Serialize};
+
+/// A cell of the canvas. The canvas goes on in every direction, so unlike a
+/// [`Position`], a point can be above or left of the origin.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Self = Self { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn offset(self, offset: Offset) -> Self {
+        Self::new(self.x + offset.x, self.y + offset.y)
+    }
+
+    /// How far it is from `other` to this point.
+    pub fn offset_from(self, other: Point) -> Offset {
+        Offset {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+
+    /// The cell this point is drawn to in a buffer whose top left cell is at
+    /// `origin`, unless it's above or left of it.
+    pub fn to_position(self, origin: Point) -> Option<Position> {
+        let x = u16::try_from(self.x - origin.x).ok()?;
+        let y = u16::try_from(self.y - origin.y).ok()?;
+        Some(Position::new(x, y))
+    }
+}
+
+impl From<(i32, i32)> for Point {
+    fn from((x, y): (i32, i32)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+/// A rectangle of cells on the canvas. Like a [`Point`], it can be anywhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Area {
+    pub x: i32,
+    pub y: i32,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Area {
+    pub const fn new(x: i32, y: i32, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The smallest area with both `a` and `b` in it.
+    pub fn between(a: Point, b: Point) -> Self {
+        Self {
+            x: a.x.min(b.x),
+            y: a.y.min(b.y),
+            width: saturate(a.x.abs_diff(b.x).saturating_add(1)),
+            height: saturate(a.y.abs_diff(b.y).saturating_add(1)),
+        }
+    }
+
+    pub const fn left(self) -> i32 {
+        self.x
+    }
+
+    pub const fn right(self) -> i32 {
+        self.x + self.width as i32
+    }
+
+    pub const fn top(self) -> i32 {
+        self.y
+    }
+
+    pub const fn bottom(self) -> i32 {
+        self.y + self.height as i32
+    }
+
+    pub const fn as_point(self) -> Point {
+        Point::new(self.x, self.y)
+    }
+
+    pub const fn area(self) -> u32 {
+        self.width as u32 * self.height as u32
+    }
+
+    pub fn contains(self, point: Point) -> bool {
+        (self.left()..self.right()).contains(&point.x)
+            && (self.top()..self.bottom()).contains(&point.y)
+    }
+
+    pub fn intersects(self, other: Area) -> bool {
+        self.left() < other.right()
+            && other.left() < self.right()
+            && self.top() < other.bottom()
+            && other.top() < self.bottom()
+    }
+
+    pub fn union(self, other: Area) -> Self {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+        Self {
+            x,
+            y,
+            width: saturate(self.right().max(other.right()).abs_diff(x)),
+            height: saturate(self.bottom().max(other.bottom()).abs_diff(y)),
+        }
+    }
+
+    pub fn offset(self, offset: Offset) -> Self {
+        Self {
+            x: self.x + offset.x,
+            y: self.y + offset.y,
+            ..self
+        }
+    }
+
+    /// The part of this area that's drawn to a buffer whose top left cell is
+    /// at `origin`. Whatever is above or left of it is cut off.
+    pub fn to_rect(self, origin: Point) -> Rect {
+        let clamp = |value: i32| value.clamp(0, u16::MAX as i32) as u16;
+        let (left, top) = (clamp(self.left() - origin.x), clamp(self.top() - origin.y));
+        Rect {
+            x: left,
+            y: top,
+            width: clamp(self.right() - origin.x) - left,
+            height: clamp(self.bottom() - origin.y) - top,
+        }
+    }
+}
+
+/// Converts a size to cells, capping it at the largest size a `u16` holds
+/// rather than wrapping around.
+fn saturate(size: u32) -> u16 {
+    u16::try_from(size).unwrap_or(u16::MAX)
+}
@@ -0,0 +1,72 @@
+use std::collections::{HashSet, VecDeque};
+
+use super::{Element, ElementId, Guide};
+
+/// How many steps can be undone before the oldest ones are forgotten.
+const HISTORY_LIMIT: usize = 256;
+
+/// The state of the canvas at one point in the undo history.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub elements: VecDeque<Element>,
+    pub guides: Vec<Guide>,
+    pub selected_elements: HashSet<ElementId>,
+}
+
+impl Snapshot {
+    /// Whether both snapshots show the same drawing, no matter what's
+    /// selected.
+    fn same_drawing(&self, other: &Snapshot) -> bool {
+        self.elements == other.elements && self.guides == other.guides
+    }
+}
+
+/// Undo / redo stacks of canvas snapshots.
+///
+/// A snapshot is recorded right before every mutation, so one entry always
+/// covers a whole user action, e.g. an entire drag rather than each of its
+/// mouse events.
+#[derive(Default)]
+pub struct History {
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+}
+
+impl History {
+    /// Records `current` as the state to return to on the next undo.
+    pub fn record(&mut self, current: Snapshot) {
+        // The last recorded mutation didn't change anything, there's no point
+        // in keeping two identical steps around.
+        if self
+            .undo_stack
+            .last()
+            .is_some_and(|last| last.same_drawing(&current))
+        {
+            return;
+        }
+
+        self.undo_stack.push(current);
+        if self.undo_stack.len() > HISTORY_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Steps back in history, returning the snapshot to restore.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        while let Some(snapshot) = self.undo_stack.pop() {
+            if !snapshot.same_drawing(&current) {
+                self.redo_stack.push(current);
+                return Some(snapshot);
+            }
+        }
+        None
+    }
+
+    /// Steps forward in history, returning the snapshot to restore.
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let snapshot = self.redo_stack.pop()?;
+        self.undo_stack.push(current);
+        Some(snapshot)
+    }
+}
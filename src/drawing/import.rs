@@ -0,0 +1,311 @@
+use super::{
+    Area, Arrowhead, BorderStyle, BoxStyle, ElementKind, LineDirection, LineStyle, Point,
+    StraightLine,
+};
+
+const HORIZONTAL: &[char] = &['─', '━', '═', '┬', '┴', '┼', '╤', '╧', '╪'];
+const VERTICAL: &[char] = &['│', '┃', '║', '├', '┤', '┼', '╟', '╢', '╫'];
+const TOP_LEFT: &[char] = &['┌', '╭', '┏', '╔'];
+const TOP_RIGHT: &[char] = &['┐', '╮', '┓', '╗'];
+const BOTTOM_LEFT: &[char] = &['└', '╰', '┗', '╚'];
+const BOTTOM_RIGHT: &[char] = &['┘', '╯', '┛', '╝'];
+
+/// Reconstructs editable elements from a plaintext drawing, such as one
+/// written by `:export`.
+///
+/// Rectangles of box-drawing characters become boxes, runs of `─`, `│`, `／`
+/// and `＼` become lines and everything else is kept as text, so no character
+/// of the input is lost. Elements are returned back to front: boxes first,
+/// then lines, then text.
+pub fn parse_plaintext(input: &str) -> Vec<ElementKind> {
+    let mut grid = Grid::new(input);
+    let mut elements = vec![];
+
+    elements.extend(grid.take_boxes());
+    elements.extend(grid.take_lines());
+    elements.extend(grid.take_text());
+
+    elements
+}
+
+struct Grid {
+    cells: Vec<Vec<char>>,
+    consumed: Vec<Vec<bool>>,
+}
+
+impl Grid {
+    fn new(input: &str) -> Self {
+        let cells = input
+            .lines()
+            .map(|line| line.trim_end().chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let consumed = cells.iter().map(|row| vec![false; row.len()]).collect();
+
+        Self { cells, consumed }
+    }
+
+    fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.cells.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    fn is(&self, x: usize, y: usize, chars: &[char]) -> bool {
+        self.get(x, y).is_some_and(|c| chars.contains(&c))
+    }
+
+    fn is_free(&self, x: usize, y: usize, chars: &[char]) -> bool {
+        self.is(x, y, chars) && !self.consumed[y][x]
+    }
+
+    fn consume(&mut self, x: usize, y: usize) {
+        self.consumed[y][x] = true;
+    }
+
+    fn positions(&self) -> Vec<(usize, usize)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| (x, y)))
+            .collect()
+    }
+
+    fn take_boxes(&mut self) -> Vec<ElementKind> {
+        let mut boxes = vec![];
+
+        for (x, y) in self.positions() {
+            if !self.is_free(x, y, TOP_LEFT) {
+                continue;
+            }
+            if let Some(area) = self.box_at(x, y) {
+                for bx in area.left()..area.right() {
+                    self.consume(bx as usize, area.y as usize);
+                    self.consume(bx as usize, (area.bottom() - 1) as usize);
+                }
+                for by in area.top()..area.bottom() {
+                    self.consume(area.x as usize, by as usize);
+                    self.consume((area.right() - 1) as usize, by as usize);
+                }
+                let border = match self.get(x, y) {
+                    Some('╭') => BorderStyle::Rounded,
+                    Some('┏') => BorderStyle::Thick,
+                    Some('╔') => BorderStyle::Double,
+                    _ => BorderStyle::Plain,
+                };
+                boxes.push(ElementKind::Box {
+                    area,
+                    style: BoxStyle {
+                        border,
+                        ..Default::default()
+                    },
+                });
+            }
+        }
+
+        boxes
+    }
+
+    /// Finds the smallest closed rectangle whose top left corner is at
+    /// `(x, y)`.
+    fn box_at(&self, x: usize, y: usize) -> Option<Area> {
+        let mut right = x + 1;
+        while self.is(right, y, HORIZONTAL) {
+            right += 1;
+        }
+        if !self.is(right, y, TOP_RIGHT) {
+            return None;
+        }
+
+        let mut bottom = y + 1;
+        while self.is(x, bottom, VERTICAL) {
+            bottom += 1;
+        }
+        if !self.is(x, bottom, BOTTOM_LEFT) || !self.is(right, bottom, BOTTOM_RIGHT) {
+            return None;
+        }
+
+        let closed = (x + 1..right).all(|bx| self.is(bx, bottom, HORIZONTAL))
+            && (y + 1..bottom).all(|by| self.is(right, by, VERTICAL));
+
+        closed.then(|| Area {
+            x: x as i32,
+            y: y as i32,
+            width: (right - x + 1) as u16,
+            height: (bottom - y + 1) as u16,
+        })
+    }
+
+    fn take_lines(&mut self) -> Vec<ElementKind> {
+        let mut lines = vec![];
+
+        for (x, y) in self.positions() {
+            let line = if self.is_free(x, y, &['─']) {
+                let mut end = x;
+                while self.is_free(end + 1, y, &['─']) {
+                    end += 1;
+                }
+                (x..=end).for_each(|x| self.consume(x, y));
+                Some((end, y, LineDirection::Right))
+            } else if self.is_free(x, y, &['│']) {
+                let mut end = y;
+                while self.is_free(x, end + 1, &['│']) {
+                    end += 1;
+                }
+                (y..=end).for_each(|y| self.consume(x, y));
+                Some((x, end, LineDirection::Down))
+            } else if self.is_free(x, y, &['＼']) {
+                let (mut ex, mut ey) = (x, y);
+                self.consume(x, y);
+                while self.is_free(ex + 2, ey + 1, &['＼']) {
+                    (ex, ey) = (ex + 2, ey + 1);
+                    self.consume(ex, ey);
+                }
+                Some((ex, ey, LineDirection::DownRight))
+            } else if self.is_free(x, y, &['／']) {
+                let (mut ex, mut ey) = (x, y);
+                self.consume(x, y);
+                while ex >= 2 && self.is_free(ex - 2, ey + 1, &['／']) {
+                    (ex, ey) = (ex - 2, ey + 1);
+                    self.consume(ex, ey);
+                }
+                Some((ex, ey, LineDirection::DownLeft))
+            } else {
+                None
+            };
+
+            if let Some((ex, ey, direction)) = line {
+                lines.push(ElementKind::Line(StraightLine {
+                    from: Point::new(x as i32, y as i32),
+                    to: Point::new(ex as i32, ey as i32),
+                    direction,
+                    style: LineStyle::Thin,
+                    start: Arrowhead::None,
+                    end: Arrowhead::None,
+                }));
+            }
+        }
+
+        lines
+    }
+
+    /// Collects the remaining characters into text blocks. Words separated by
+    /// a single space stay together, and fragments that start in the same
+    /// column on consecutive rows are merged into multi-line text.
+    fn take_text(&mut self) -> Vec<ElementKind> {
+        let mut fragments: Vec<(usize, usize, String)> = vec![];
+
+        for y in 0..self.cells.len() {
+            let mut x = 0;
+            while x < self.cells[y].len() {
+                if self.consumed[y][x] || self.cells[y][x] == ' ' {
+                    x += 1;
+                    continue;
+                }
+
+                let start = x;
+                let mut end = x;
+                while x < self.cells[y].len() {
+                    if !self.consumed[y][x] && self.cells[y][x] != ' ' {
+                        end = x;
+                    } else if x > end + 1 || self.consumed[y][x] {
+                        break;
+                    }
+                    x += 1;
+                }
+
+                fragments.push((start, y, self.cells[y][start..=end].iter().collect()));
+                (start..=end).for_each(|x| self.consume(x, y));
+                x = end + 1;
+            }
+        }
+
+        let mut blocks: Vec<(usize, usize, Vec<String>)> = vec![];
+
+        for (x, y, content) in fragments {
+            if let Some((_, _, lines)) = blocks
+                .iter_mut()
+                .find(|(bx, by, lines)| *bx == x && by + lines.len() == y)
+            {
+                lines.push(content);
+            } else {
+                blocks.push((x, y, vec![content]));
+            }
+        }
+
+        blocks
+            .into_iter()
+            .map(|(x, y, lines)| ElementKind::Text {
+                area: Area {
+                    x: x as i32,
+                    y: y as i32,
+                    width: lines
+                        .iter()
+                        .map(|l| l.chars().count())
+                        .max()
+                        .unwrap_or_default() as u16,
+                    height: lines.len() as u16,
+                },
+                content: lines.join("\n"),
+                alignment: Default::default(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn areas(elements: &[ElementKind]) -> Vec<(String, Area)> {
+        elements.iter().map(|el| (el.name(), el.area())).collect()
+    }
+
+    #[test]
+    fn test_import_box_with_text() {
+        let elements = parse_plaintext("┌─────┐\n│ Hi  │\n└─────┘\n");
+        assert_eq!(
+            areas(&elements),
+            vec![
+                ("Box".into(), Area::new(0, 0, 7, 3)),
+                ("Text \"Hi\"".into(), Area::new(2, 1, 2, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_lines() {
+        let elements = parse_plaintext("───  │\n     │\n＼ ／\n");
+        assert_eq!(
+            areas(&elements),
+            vec![
+                ("Line".into(), Area::new(0, 0, 3, 1)),
+                ("Line".into(), Area::new(5, 0, 1, 2)),
+                ("Line".into(), Area::new(0, 2, 1, 1)),
+                ("Line".into(), Area::new(2, 2, 1, 1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_open_box_falls_back_to_text() {
+        let elements = parse_plaintext("┌──┐\n│  \n└──┘\n");
+        assert!(elements
+            .iter()
+            .all(|el| !matches!(el, ElementKind::Box { .. })));
+        assert!(elements
+            .iter()
+            .any(|el| matches!(el, ElementKind::Text { .. })));
+    }
+
+    #[test]
+    fn test_import_multiline_text() {
+        let elements = parse_plaintext("foo bar\nbaz\n\nqux");
+        assert_eq!(
+            areas(&elements),
+            vec![
+                ("Text \"foo bar\nbaz\"".into(), Area::new(0, 0, 7, 2)),
+                ("Text \"qux\"".into(), Area::new(0, 3, 3, 1)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect},
+};
+
+/// How heavy the line leaving a cell towards each side is: up, right, down
+/// and left. 0 means there's none, 1 thin, 2 thick and 3 double.
+type Arms = [u8; 4];
+
+/// Every box-drawing glyph that can be joined with others, and its arms. The
+/// rounded corners come last, so joining never produces them.
+const GLYPHS: &[(char, Arms)] = &[
+    ('─', [0, 1, 0, 1]),
+    ('━', [0, 2, 0, 2]),
+    ('│', [1, 0, 1, 0]),
+    ('┃', [2, 0, 2, 0]),
+    ('┌', [0, 1, 1, 0]),
+    ('┍', [0, 2, 1, 0]),
+    ('┎', [0, 1, 2, 0]),
+    ('┏', [0, 2, 2, 0]),
+    ('┐', [0, 0, 1, 1]),
+    ('┑', [0, 0, 1, 2]),
+    ('┒', [0, 0, 2, 1]),
+    ('┓', [0, 0, 2, 2]),
+    ('└', [1, 1, 0, 0]),
+    ('┕', [1, 2, 0, 0]),
+    ('┖', [2, 1, 0, 0]),
+    ('┗', [2, 2, 0, 0]),
+    ('┘', [1, 0, 0, 1]),
+    ('┙', [1, 0, 0, 2]),
+    ('┚', [2, 0, 0, 1]),
+    ('┛', [2, 0, 0, 2]),
+    ('├', [1, 1, 1, 0]),
+    ('┝', [1, 2, 1, 0]),
+    ('┞', [2, 1, 1, 0]),
+    ('┟', [1, 1, 2, 0]),
+    ('┠', [2, 1, 2, 0]),
+    ('┡', [2, 2, 1, 0]),
+    ('┢', [1, 2, 2, 0]),
+    ('┣', [2, 2, 2, 0]),
+    ('┤', [1, 0, 1, 1]),
+    ('┥', [1, 0, 1, 2]),
+    ('┦', [2, 0, 1, 1]),
+    ('┧', [1, 0, 2, 1]),
+    ('┨', [2, 0, 2, 1]),
+    ('┩', [2, 0, 1, 2]),
+    ('┪', [1, 0, 2, 2]),
+    ('┫', [2, 0, 2, 2]),
+    ('┬', [0, 1, 1, 1]),
+    ('┭', [0, 1, 1, 2]),
+    ('┮', [0, 2, 1, 1]),
+    ('┯', [0, 2, 1, 2]),
+    ('┰', [0, 1, 2, 1]),
+    ('┱', [0, 1, 2, 2]),
+    ('┲', [0, 2, 2, 1]),
+    ('┳', [0, 2, 2, 2]),
+    ('┴', [1, 1, 0, 1]),
+    ('┵', [1, 1, 0, 2]),
+    ('┶', [1, 2, 0, 1]),
+    ('┷', [1, 2, 0, 2]),
+    ('┸', [2, 1, 0, 1]),
+    ('┹', [2, 1, 0, 2]),
+    ('┺', [2, 2, 0, 1]),
+    ('┻', [2, 2, 0, 2]),
+    ('┼', [1, 1, 1, 1]),
+    ('┽', [1, 1, 1, 2]),
+    ('┾', [1, 2, 1, 1]),
+    ('┿', [1, 2, 1, 2]),
+    ('╀', [2, 1, 1, 1]),
+    ('╁', [1, 1, 2, 1]),
+    ('╂', [2, 1, 2, 1]),
+    ('╃', [2, 1, 1, 2]),
+    ('╄', [2, 2, 1, 1]),
+    ('╅', [1, 1, 2, 2]),
+    ('╆', [1, 2, 2, 1]),
+    ('╇', [2, 2, 1, 2]),
+    ('╈', [1, 2, 2, 2]),
+    ('╉', [2, 1, 2, 2]),
+    ('╊', [2, 2, 2, 1]),
+    ('╋', [2, 2, 2, 2]),
+    ('═', [0, 3, 0, 3]),
+    ('║', [3, 0, 3, 0]),
+    ('╒', [0, 3, 1, 0]),
+    ('╓', [0, 1, 3, 0]),
+    ('╔', [0, 3, 3, 0]),
+    ('╕', [0, 0, 1, 3]),
+    ('╖', [0, 0, 3, 1]),
+    ('╗', [0, 0, 3, 3]),
+    ('╘', [1, 3, 0, 0]),
+    ('╙', [3, 1, 0, 0]),
+    ('╚', [3, 3, 0, 0]),
+    ('╛', [1, 0, 0, 3]),
+    ('╜', [3, 0, 0, 1]),
+    ('╝', [3, 0, 0, 3]),
+    ('╞', [1, 3, 1, 0]),
+    ('╟', [3, 1, 3, 0]),
+    ('╠', [3, 3, 3, 0]),
+    ('╡', [1, 0, 1, 3]),
+    ('╢', [3, 0, 3, 1]),
+    ('╣', [3, 0, 3, 3]),
+    ('╤', [0, 3, 1, 3]),
+    ('╥', [0, 1, 3, 1]),
+    ('╦', [0, 3, 3, 3]),
+    ('╧', [1, 3, 0, 3]),
+    ('╨', [3, 1, 0, 1]),
+    ('╩', [3, 3, 0, 3]),
+    ('╪', [1, 3, 1, 3]),
+    ('╫', [3, 1, 3, 1]),
+    ('╬', [3, 3, 3, 3]),
+    ('╭', [0, 1, 1, 0]),
+    ('╮', [0, 0, 1, 1]),
+    ('╯', [1, 0, 0, 1]),
+    ('╰', [1, 1, 0, 0]),
+];
+
+fn arms(symbol: &str) -> Option<Arms> {
+    let mut chars = symbol.chars();
+    let c = chars.next().filter(|_| chars.next().is_none())?;
+    GLYPHS
+        .iter()
+        .find(|(glyph, _)| *glyph == c)
+        .map(|(_, arms)| *arms)
+}
+
+fn glyph(arms: Arms) -> Option<char> {
+    GLYPHS
+        .iter()
+        .find(|(_, glyph_arms)| *glyph_arms == arms)
+        .map(|(glyph, _)| *glyph)
+}
+
+/// The ASCII version of a box-drawing glyph: `-` or `|` for a straight
+/// line, and `+` wherever lines turn or meet.
+pub fn ascii(symbol: &str) -> Option<char> {
+    let [up, right, down, left] = arms(symbol)?;
+    Some(match (up + down > 0, left + right > 0) {
+        (false, _) => '-',
+        (_, false) => '|',
+        _ => '+',
+    })
+}
+
+/// Joins the arms of two glyphs drawn on top of each other. Where both have
+/// an arm, the one on top wins. Double lines don't mix with thick ones, so
+/// if there's no glyph for that, the one below takes on the weight of the
+/// one on top.
+fn join(below: Arms, above: Arms) -> Option<char> {
+    let joined = |below: Arms| {
+        let mut arms = above;
+        for (arm, below) in arms.iter_mut().zip(below) {
+            if *arm == 0 {
+                *arm = below;
+            }
+        }
+        glyph(arms)
+    };
+    let weight = above.into_iter().find(|arm| *arm != 0)?;
+
+    joined(below).or_else(|| joined(below.map(|arm| arm.min(1) * weight)))
+}
+
+/// Whether the neighbour of `position` towards `side` continues a line
+/// leaving through that side. Anything that isn't blank or a box-drawing
+/// glyph, like an arrowhead, counts as continuing it.
+fn continues(buffer: &Buffer, position: Position, side: usize) -> bool {
+    let (dx, dy) = [(0, -1), (1, 0), (0, 1), (-1, 0)][side];
+    let neighbour = position
+        .x
+        .checked_add_signed(dx)
+        .zip(position.y.checked_add_signed(dy))
+        .and_then(|(x, y)| buffer.cell((x, y)));
+
+    match neighbour.map(|cell| cell.symbol()) {
+        None | Some(" ") => false,
+        Some(symbol) => arms(symbol).is_none_or(|arms| arms[(side + 2) % 4] != 0),
+    }
+}
+
+/// Draws with `draw`, and then joins the lines it drew inside of `area` with
+/// the ones that were already there, so that crossing lines become `┼`, and
+/// a line ending on a border becomes `├`, `┤`, `┬` or `┴`.
+pub fn draw_joined(buffer: &mut Buffer, area: Rect, draw: impl FnOnce(&mut Buffer)) {
+    let area = area.intersection(buffer.area);
+    let below = area
+        .positions()
+        .map(|position| buffer.cell(position).and_then(|cell| arms(cell.symbol())))
+        .collect::<Vec<_>>();
+
+    draw(buffer);
+
+    let mut joined = vec![];
+    for (position, below) in area.positions().zip(below) {
+        let Some(cell) = buffer.cell_mut(position) else {
+            continue;
+        };
+        let (Some(below), Some(above)) = (below, arms(cell.symbol())) else {
+            continue;
+        };
+        if below == above {
+            continue;
+        }
+        if let Some(symbol) = join(below, above) {
+            cell.set_char(symbol);
+            joined.push((position, below, above));
+        }
+    }
+
+    // Arms only one of the glyphs had, which don't lead anywhere, are the
+    // end of a line that just touches the other one
+    for (position, below, above) in joined {
+        let Some(mut arms) = buffer.cell(position).and_then(|cell| arms(cell.symbol())) else {
+            continue;
+        };
+        for side in 0..4 {
+            if (below[side] == 0 || above[side] == 0) && !continues(buffer, position, side) {
+                arms[side] = 0;
+            }
+        }
+        if let Some(symbol) =
+            glyph(arms).filter(|_| arms.iter().filter(|arm| **arm != 0).count() >= 2)
+        {
+            if let Some(cell) = buffer.cell_mut(position) {
+                cell.set_char(symbol);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join() {
+        assert_eq!(join([1, 0, 1, 0], [0, 1, 0, 1]), Some('┼'));
+        assert_eq!(join([0, 1, 1, 0], [0, 2, 0, 2]), Some('┯'));
+        assert_eq!(join([3, 0, 3, 0], [0, 1, 0, 1]), Some('╫'));
+        assert_eq!(join([3, 0, 3, 0], [0, 2, 0, 2]), Some('╋'));
+    }
+}
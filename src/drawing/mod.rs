@@ -1,7 +1,21 @@
 pub mod canvas;
+pub mod connector;
+pub mod document;
 pub mod elements;
+pub mod export;
+pub mod geometry;
+pub mod history;
+pub mod import;
+pub mod junction;
 pub mod operation;
+pub mod property;
+pub mod snap;
 
 pub use canvas::*;
+pub use connector::*;
+pub use document::*;
 pub use elements::*;
+pub use geometry::*;
+pub use history::*;
 pub use operation::*;
+pub use snap::*;
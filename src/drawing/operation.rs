@@ -1,29 +1,56 @@
-use ratatui::layout::{Offset, Position, Rect};
+use ratatui::layout::Offset;
 use tui_textarea::TextArea;
 
-use super::{Element, StraightLine};
+use super::{elements::scale_rect, Area, Element, ElementKind, Point, Polyline, StraightLine};
 
 #[derive(Clone)]
 pub enum Operation {
     Selection {
-        origin: Position,
-        second: Position,
+        origin: Point,
+        second: Point,
     },
     Move {
-        origin: Position,
-        second: Position,
+        origin: Point,
+        second: Point,
     },
+    /// Drags a corner of `bounds`, the bounding box of everything selected,
+    /// scaling all of it along.
     Resize {
         direction: Direction,
-        origin: Position,
-        second: Position,
+        origin: Point,
+        second: Point,
+        bounds: Area,
+        /// Keeps the aspect ratio of `bounds`.
+        proportional: bool,
+        /// Resizes around the center of `bounds` instead of the opposite
+        /// corner.
+        centered: bool,
     },
     MoveLineHandle {
         handle: LineHandle,
-        pos: Position,
+        pos: Point,
+    },
+    /// Drags one vertex of a polyline.
+    MoveVertex {
+        index: usize,
+        pos: Point,
+    },
+    /// A polyline being drawn, one click per vertex.
+    DrawPolyline {
+        points: Vec<Point>,
+        cursor: Point,
     },
     EditText {
-        textarea: TextArea<'static>,
+        textarea: Box<TextArea<'static>>,
+    },
+    /// A paint or eraser stroke, which modifies the canvas as it goes.
+    Stroke {
+        last: Point,
+    },
+    /// Drags the guide at `index` of the canvas's guides, which modifies the
+    /// canvas as it goes.
+    MoveGuide {
+        index: usize,
     },
 }
 
@@ -41,83 +68,209 @@ pub enum Direction {
     BottomRight,
 }
 
+impl Direction {
+    /// The resize handle of `area` at `point`, if there is one. The handles
+    /// sit diagonally outside its corners.
+    pub fn of_handle(area: Area, point: Point) -> Option<Self> {
+        let (left, top) = (area.left() - 1, area.top() - 1);
+        if point == Point::new(left, top) {
+            Some(Self::TopLeft)
+        } else if point == Point::new(area.right(), top) {
+            Some(Self::TopRight)
+        } else if point == Point::new(left, area.bottom()) {
+            Some(Self::BottomLeft)
+        } else if point == Point::new(area.right(), area.bottom()) {
+            Some(Self::BottomRight)
+        } else {
+            None
+        }
+    }
+
+    /// Which way the corner being resized is from its handle.
+    pub fn inward(self) -> Offset {
+        match self {
+            Self::TopLeft => Offset { x: 1, y: 1 },
+            Self::TopRight => Offset { x: -1, y: 1 },
+            Self::BottomLeft => Offset { x: 1, y: -1 },
+            Self::BottomRight => Offset { x: -1, y: -1 },
+        }
+    }
+}
+
 impl Operation {
-    fn transform_area(&self, area: &Rect) -> Rect {
+    /// Where `area` ends up once the operation is applied.
+    pub fn transform_area(&self, area: &Area) -> Area {
         match self {
-            Operation::Move { origin, second } => area.offset(Offset {
-                x: second.x as i32 - origin.x as i32,
-                y: second.y as i32 - origin.y as i32,
-            }),
-            Operation::Resize {
-                direction,
-                origin,
-                second,
-            } => match direction {
-                Direction::TopLeft => Rect {
-                    x: (area.x as i32
-                        + (second.x as i32 - origin.x as i32).min(area.width as i32 - 2))
-                        as u16,
-                    y: (area.y as i32
-                        + (second.y as i32 - origin.y as i32).min(area.height as i32 - 2))
-                        as u16,
-                    width: (area.width as i32 - (second.x as i32 - origin.x as i32)).max(1) as u16,
-                    height: (area.height as i32 - (second.y as i32 - origin.y as i32)).max(1)
-                        as u16,
-                },
-                Direction::TopRight => Rect {
-                    x: area.x,
-                    y: (area.y as i32
-                        + (second.y as i32 - origin.y as i32).min(area.height as i32 - 2))
-                        as u16,
-                    width: (area.width as i32 + (second.x as i32 - origin.x as i32)).max(1) as u16,
-                    height: (area.height as i32 - (second.y as i32 - origin.y as i32)).max(1)
-                        as u16,
-                },
-                Direction::BottomLeft => Rect {
-                    x: (area.x as i32
-                        + (second.x as i32 - origin.x as i32).min(area.width as i32 - 2))
-                        as u16,
-                    y: area.y,
-                    width: (area.width as i32 - (second.x as i32 - origin.x as i32)).max(1) as u16,
-                    height: (area.height as i32 + (second.y as i32 - origin.y as i32)).max(1)
-                        as u16,
-                },
-                Direction::BottomRight => Rect {
-                    x: area.x,
-                    y: area.y,
-                    width: (area.width as i32 + (second.x as i32 - origin.x as i32)).max(1) as u16,
-                    height: (area.height as i32 + (second.y as i32 - origin.y as i32)).max(1)
-                        as u16,
-                },
-            },
+            Operation::Move { origin, second } => area.offset(second.offset_from(*origin)),
+            Operation::Resize { bounds, .. } if area == bounds => self.resized_bounds(),
+            Operation::Resize { bounds, .. } => scale_rect(*area, *bounds, self.resized_bounds()),
             _ => *area,
         }
     }
 
+    /// The bounds of a resize, once resized.
+    fn resized_bounds(&self) -> Area {
+        let Operation::Resize {
+            direction,
+            origin,
+            second,
+            bounds,
+            proportional,
+            centered,
+        } = self
+        else {
+            return Area::default();
+        };
+
+        // Resizing around the center moves both sides at once
+        let sides = if *centered { 2 } else { 1 };
+        let inward = direction.inward();
+        let (old_width, old_height) = (bounds.width as i32, bounds.height as i32);
+        let mut width = (old_width - inward.x * (second.x - origin.x) * sides).max(1);
+        let mut height = (old_height - inward.y * (second.y - origin.y) * sides).max(1);
+
+        // Whichever side changed more, relative to its length, sets the scale
+        if *proportional {
+            if (width - old_width).abs() * old_height >= (height - old_height).abs() * old_width {
+                height = ((width * old_height + old_width / 2) / old_width).max(1);
+            } else {
+                width = ((height * old_width + old_height / 2) / old_height).max(1);
+            }
+        }
+
+        let place = |start: i32, end: i32, old: i32, new: i32, inward: i32| {
+            if *centered {
+                start + (old - new) / 2
+            } else if inward > 0 {
+                end - new
+            } else {
+                start
+            }
+        };
+        Area {
+            x: place(bounds.left(), bounds.right(), old_width, width, inward.x),
+            y: place(bounds.top(), bounds.bottom(), old_height, height, inward.y),
+            width: width as u16,
+            height: height as u16,
+        }
+    }
+
     pub fn apply_transform(&self, element: &Element) -> Option<Element> {
-        match element {
-            Element::Box { area } => Some(Element::Box {
+        let kind = match &element.kind {
+            ElementKind::Box { area, style } => Some(ElementKind::Box {
                 area: self.transform_area(area),
+                style: style.clone(),
             }),
-            Element::Text { area, content } => Some(Element::Text {
+            ElementKind::Ellipse { area } => Some(ElementKind::Ellipse {
+                area: self.transform_area(area),
+            }),
+            ElementKind::Diamond { area } => Some(ElementKind::Diamond {
+                area: self.transform_area(area),
+            }),
+            ElementKind::Text {
+                area,
+                content,
+                alignment,
+            } => Some(ElementKind::Text {
                 area: self.transform_area(area),
                 content: content.clone(),
+                alignment: *alignment,
             }),
-            Element::Line(line) => match self {
+            ElementKind::Line(line) => match self {
                 Operation::Move { origin, second } => {
-                    let area = line.area();
-                    Some(Element::Line(line.offset(Offset {
-                        x: (second.x as i32 - origin.x as i32).max(-(area.x as i32)),
-                        y: (second.y as i32 - origin.y as i32).max(-(area.y as i32)),
-                    })))
+                    Some(ElementKind::Line(line.offset(second.offset_from(*origin))))
                 }
                 Operation::MoveLineHandle { handle, pos } => match handle {
-                    super::LineHandle::First => StraightLine::new(line.to, *pos),
-                    super::LineHandle::Second => StraightLine::new(line.from, *pos),
+                    super::LineHandle::First => {
+                        StraightLine::new(line.to, *pos).map(|new| new.styled_like(line, true))
+                    }
+                    super::LineHandle::Second => {
+                        StraightLine::new(line.from, *pos).map(|new| new.styled_like(line, false))
+                    }
+                }
+                .map(ElementKind::Line),
+                Operation::Resize { bounds, .. } => {
+                    Some(element.kind.scale(*bounds, self.resized_bounds()))
                 }
-                .map(Element::Line),
                 _ => None,
             },
-        }
+            ElementKind::Polyline(polyline) => match self {
+                Operation::MoveVertex { index, pos } => {
+                    let mut points = polyline.points.clone();
+                    *points.get_mut(*index)? = *pos;
+                    Some(ElementKind::Polyline(Polyline {
+                        points,
+                        ..polyline.clone()
+                    }))
+                }
+                _ => {
+                    let area = element.area();
+                    Some(element.kind.scale(area, self.transform_area(&area)))
+                }
+            },
+            // Bound ends follow their elements, so only the free ones move
+            ElementKind::Connector(..) => match self {
+                Operation::Move { origin, second } => {
+                    Some(element.kind.offset(second.offset_from(*origin)))
+                }
+                _ => None,
+            },
+            ElementKind::Paint { .. } | ElementKind::Group { .. } => {
+                let area = element.area();
+                Some(element.kind.scale(area, self.transform_area(&area)))
+            }
+        };
+
+        kind.map(|kind| Element::new(element.id, kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing::ElementId;
+
+    #[test]
+    fn test_resize_selection() {
+        let bounds = Area::new(0, 0, 10, 2);
+        let resize = Operation::Resize {
+            direction: Direction::BottomRight,
+            origin: Point::new(10, 2),
+            second: Point::new(20, 2),
+            bounds,
+            proportional: true,
+            centered: false,
+        };
+        let transform = |kind: ElementKind| {
+            resize
+                .apply_transform(&Element::new(ElementId(0), kind))
+                .unwrap()
+                .kind
+        };
+
+        assert_eq!(resize.transform_area(&bounds), Area::new(0, 0, 20, 4));
+        assert_eq!(
+            transform(ElementKind::Ellipse {
+                area: Area::new(0, 0, 4, 2)
+            })
+            .area(),
+            Area::new(0, 0, 7, 4)
+        );
+        let line = StraightLine::new(Point::new(6, 0), Point::new(9, 0)).unwrap();
+        assert!(matches!(
+            transform(ElementKind::Line(line)),
+            ElementKind::Line(StraightLine { from, to, .. })
+                if from == Point::new(13, 0) && to == Point::new(19, 0)
+        ));
+
+        let resize = Operation::Resize {
+            direction: Direction::TopLeft,
+            origin: Point::new(-1, -1),
+            second: Point::new(-3, -1),
+            bounds,
+            proportional: false,
+            centered: true,
+        };
+        assert_eq!(resize.transform_area(&bounds), Area::new(-2, 0, 14, 2));
     }
 }
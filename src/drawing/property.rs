@@ -0,0 +1,333 @@
+use std::str::FromStr;
+
+use ratatui::layout::Offset;
+
+use super::{cycle, Area, Connector, ElementKind, Polyline, StraightLine};
+
+/// A property of an element that can be edited in the style editor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Property {
+    X,
+    Y,
+    Width,
+    Height,
+    LineStyle,
+    Start,
+    End,
+    Routing,
+    Border,
+    Title,
+    TitleAlignment,
+    Shadow,
+    TextAlignment,
+}
+
+impl Property {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::X => "X",
+            Self::Y => "Y",
+            Self::Width => "Width",
+            Self::Height => "Height",
+            Self::LineStyle => "Style",
+            Self::Start => "Start",
+            Self::End => "End",
+            Self::Routing => "Route",
+            Self::Border => "Type",
+            Self::Title => "Title",
+            Self::TitleAlignment => "Align",
+            Self::Shadow => "Type",
+            Self::TextAlignment => "Align",
+        }
+    }
+
+    /// Whether the value is typed in, rather than picked from a fixed set of
+    /// options.
+    pub fn is_typed(self) -> bool {
+        matches!(
+            self,
+            Self::X | Self::Y | Self::Width | Self::Height | Self::Title
+        )
+    }
+
+    /// Whether `kind` is left alone when the property is changed. Connectors
+    /// are placed by what they're bound to, so they have no position of their
+    /// own to edit.
+    pub fn is_read_only(self, kind: &ElementKind) -> bool {
+        matches!(
+            (self, kind),
+            (
+                Self::X | Self::Y | Self::Width | Self::Height,
+                ElementKind::Connector(..)
+            )
+        )
+    }
+
+    /// The value of the property for `kind`, if it has it.
+    pub fn value(self, kind: &ElementKind) -> Option<String> {
+        let area = kind.area();
+        match (self, kind) {
+            (Self::X, _) => Some(area.x.to_string()),
+            (Self::Y, _) => Some(area.y.to_string()),
+            (Self::Width, _) => Some(area.width.to_string()),
+            (Self::Height, _) => Some(area.height.to_string()),
+            (
+                Self::LineStyle,
+                ElementKind::Line(StraightLine { style, .. })
+                | ElementKind::Polyline(Polyline { style, .. })
+                | ElementKind::Connector(Connector { style, .. }),
+            ) => Some(style.to_string()),
+            (
+                Self::Start,
+                ElementKind::Line(StraightLine { start, .. })
+                | ElementKind::Polyline(Polyline { start, .. })
+                | ElementKind::Connector(Connector { start, .. }),
+            ) => Some(start.to_string()),
+            (
+                Self::End,
+                ElementKind::Line(StraightLine { end, .. })
+                | ElementKind::Polyline(Polyline { end, .. })
+                | ElementKind::Connector(Connector { end, .. }),
+            ) => Some(end.to_string()),
+            (Self::Routing, ElementKind::Connector(connector)) => {
+                Some(connector.routing.to_string())
+            }
+            (Self::Border, ElementKind::Box { style, .. }) => Some(style.border.to_string()),
+            (Self::Title, ElementKind::Box { style, .. }) => Some(style.title.clone()),
+            (Self::TitleAlignment, ElementKind::Box { style, .. }) => {
+                Some(style.title_alignment.to_string())
+            }
+            (Self::Shadow, ElementKind::Box { style, .. }) => Some(style.shadow.to_string()),
+            (Self::TextAlignment, ElementKind::Text { alignment, .. }) => {
+                Some(alignment.to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// Counts a number up or down by `by`, or steps through the options.
+    pub fn step(self, kind: &mut ElementKind, by: isize) {
+        if self.is_read_only(kind) {
+            return;
+        }
+        let step = |value: u16| value.saturating_add_signed(by as i16).max(1);
+        match (self, kind) {
+            (Self::X, kind) => *kind = kind.offset(Offset { x: by as i32, y: 0 }),
+            (Self::Y, kind) => *kind = kind.offset(Offset { x: 0, y: by as i32 }),
+            (Self::Width, kind) => resize(kind, |area| Area {
+                width: step(area.width),
+                ..area
+            }),
+            (Self::Height, kind) => resize(kind, |area| Area {
+                height: step(area.height),
+                ..area
+            }),
+            (
+                Self::LineStyle,
+                ElementKind::Line(StraightLine { style, .. })
+                | ElementKind::Polyline(Polyline { style, .. })
+                | ElementKind::Connector(Connector { style, .. }),
+            ) => *style = cycle(*style, by),
+            (
+                Self::Start,
+                ElementKind::Line(StraightLine { start, .. })
+                | ElementKind::Polyline(Polyline { start, .. })
+                | ElementKind::Connector(Connector { start, .. }),
+            ) => *start = cycle(*start, by),
+            (
+                Self::End,
+                ElementKind::Line(StraightLine { end, .. })
+                | ElementKind::Polyline(Polyline { end, .. })
+                | ElementKind::Connector(Connector { end, .. }),
+            ) => *end = cycle(*end, by),
+            (Self::Routing, ElementKind::Connector(connector)) => {
+                connector.routing = cycle(connector.routing, by)
+            }
+            (Self::Border, ElementKind::Box { style, .. }) => {
+                style.border = cycle(style.border, by)
+            }
+            (Self::TitleAlignment, ElementKind::Box { style, .. }) => {
+                style.title_alignment = cycle(style.title_alignment, by)
+            }
+            (Self::Shadow, ElementKind::Box { style, .. }) => {
+                style.shadow = cycle(style.shadow, by)
+            }
+            (Self::TextAlignment, ElementKind::Text { alignment, .. }) => {
+                *alignment = cycle(*alignment, by)
+            }
+            _ => (),
+        }
+    }
+
+    /// Sets a typed property from `input`. Fails if `input` isn't a valid
+    /// value for it.
+    pub fn set(self, kind: &mut ElementKind, input: &str) -> Result<(), String> {
+        if self.is_read_only(kind) {
+            return Ok(());
+        }
+        match (self, kind) {
+            (Self::X, kind) => {
+                let x: i32 = self.parse(input)?;
+                *kind = kind.offset(Offset {
+                    x: x - kind.area().x,
+                    y: 0,
+                })
+            }
+            (Self::Y, kind) => {
+                let y: i32 = self.parse(input)?;
+                *kind = kind.offset(Offset {
+                    x: 0,
+                    y: y - kind.area().y,
+                })
+            }
+            (Self::Width, kind) => {
+                let width = self.parse::<u16>(input)?.max(1);
+                resize(kind, |area| Area { width, ..area })
+            }
+            (Self::Height, kind) => {
+                let height = self.parse::<u16>(input)?.max(1);
+                resize(kind, |area| Area { height, ..area })
+            }
+            (Self::Title, ElementKind::Box { style, .. }) => style.title = input.to_string(),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn parse<T: FromStr>(self, input: &str) -> Result<T, String> {
+        input
+            .trim()
+            .parse()
+            .map_err(|_| format!("\"{input}\" is not a valid {}", self.name()))
+    }
+}
+
+fn resize(kind: &mut ElementKind, to: impl FnOnce(Area) -> Area) {
+    let area = kind.area();
+    *kind = kind.scale(area, to(area));
+}
+
+/// The sections of the style editor, with the properties that every one of
+/// `kinds` has. There's no position to edit if they're all connectors.
+pub fn sections(kinds: &[&ElementKind]) -> Vec<(&'static str, Vec<Property>)> {
+    use Property::*;
+
+    if kinds.is_empty() {
+        return vec![];
+    }
+
+    let mut sections = vec![];
+    if !kinds
+        .iter()
+        .all(|kind| matches!(kind, ElementKind::Connector(..)))
+    {
+        sections.push(("Position", vec![X, Y, Width, Height]));
+    }
+    if kinds.iter().all(|kind| {
+        matches!(
+            kind,
+            ElementKind::Line(..) | ElementKind::Polyline(..) | ElementKind::Connector(..)
+        )
+    }) {
+        sections.push(("Line", vec![LineStyle, Start, End]));
+    }
+    if kinds
+        .iter()
+        .all(|kind| matches!(kind, ElementKind::Connector(..)))
+    {
+        sections.push(("Connector", vec![Routing]));
+    }
+    if kinds
+        .iter()
+        .all(|kind| matches!(kind, ElementKind::Box { .. }))
+    {
+        sections.push(("Border", vec![Border, Title, TitleAlignment]));
+        sections.push(("Shadow", vec![Shadow]));
+    }
+    if kinds
+        .iter()
+        .all(|kind| matches!(kind, ElementKind::Text { .. }))
+    {
+        sections.push(("Text", vec![TextAlignment]));
+    }
+    sections
+}
+
+/// The value `property` has for all of `kinds` it can be changed for, or
+/// `None` if they differ.
+pub fn common_value(property: Property, kinds: &[&ElementKind]) -> Option<String> {
+    let mut values = kinds
+        .iter()
+        .filter(|kind| !property.is_read_only(kind))
+        .map(|kind| property.value(kind));
+    let first = values.next()??;
+    values
+        .all(|value| value.as_ref() == Some(&first))
+        .then_some(first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::drawing::{BoxStyle, Endpoint, Point, Shadow};
+
+    #[test]
+    fn test_mixed_values() {
+        let small = ElementKind::Box {
+            area: Area::new(0, 0, 4, 2),
+            style: BoxStyle::default(),
+        };
+        let mut large = ElementKind::Box {
+            area: Area::new(0, 5, 8, 2),
+            style: BoxStyle::default(),
+        };
+        Property::Shadow.step(&mut large, 1);
+        assert!(matches!(
+            &large,
+            ElementKind::Box { style, .. } if style.shadow == Shadow::Light
+        ));
+
+        let kinds = [&small, &large];
+        assert_eq!(common_value(Property::X, &kinds), Some("0".into()));
+        assert_eq!(common_value(Property::Width, &kinds), None);
+        assert_eq!(common_value(Property::Shadow, &kinds), None);
+
+        Property::Width.set(&mut large, "4").unwrap();
+        assert_eq!(large.area(), Area::new(0, 5, 4, 2));
+        assert!(Property::Width.set(&mut large, "wide").is_err());
+    }
+
+    #[test]
+    fn test_position_with_connectors() {
+        let mut text = ElementKind::Text {
+            area: Area::new(0, 0, 5, 1),
+            content: "hello".into(),
+            alignment: Default::default(),
+        };
+        Property::Width.set(&mut text, "9").unwrap();
+        Property::X.step(&mut text, 1);
+        assert_eq!(text.area(), Area::new(1, 0, 9, 1));
+
+        let mut connector = ElementKind::Connector(Connector::new(
+            Endpoint::Free {
+                position: Point::new(0, 0),
+            },
+            Endpoint::Free {
+                position: Point::new(4, 0),
+            },
+        ));
+        assert!(sections(&[&connector])
+            .iter()
+            .all(|(name, _)| *name != "Position"));
+        assert_eq!(sections(&[&text, &connector])[0].0, "Position");
+        assert_eq!(
+            common_value(Property::X, &[&text, &connector]),
+            Some("1".into())
+        );
+
+        let before = connector.clone();
+        Property::X.set(&mut connector, "7").unwrap();
+        Property::Width.step(&mut connector, 1);
+        assert!(connector == before);
+    }
+}
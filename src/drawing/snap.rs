@@ -0,0 +1,111 @@
+use ratatui::layout::Offset;
+use serde::{Deserialize, Serialize};
+
+use super::{Area, Point};
+
+/// How close, in cells, an edge has to come to a guide or to the edge of
+/// another element to snap to it.
+const SNAP_DISTANCE: i32 = 2;
+
+/// A line across the whole canvas that edges snap to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Guide {
+    /// Runs along row `y`.
+    Horizontal { y: i32 },
+    /// Runs along column `x`.
+    Vertical { x: i32 },
+}
+
+impl Guide {
+    /// Whether the guide runs through `point`.
+    pub fn contains(self, point: Point) -> bool {
+        match self {
+            Self::Horizontal { y } => point.y == y,
+            Self::Vertical { x } => point.x == x,
+        }
+    }
+
+    /// Moves the guide onto the row or column of `point`.
+    pub fn move_to(&mut self, point: Point) {
+        match self {
+            Self::Horizontal { y } => *y = point.y,
+            Self::Vertical { x } => *x = point.x,
+        }
+    }
+}
+
+/// Where edges snap to while moving, resizing or drawing elements: guides,
+/// the edges of other elements and, if nothing of that is close, the lines
+/// of a grid.
+#[derive(Default)]
+pub struct Snap {
+    /// How many cells apart the lines of the grid are. Below 2, there's no
+    /// grid.
+    grid: u16,
+    columns: Vec<i32>,
+    rows: Vec<i32>,
+}
+
+impl Snap {
+    pub fn new(grid: u16, guides: &[Guide], others: impl IntoIterator<Item = Area>) -> Self {
+        let mut snap = Self {
+            grid,
+            ..Default::default()
+        };
+        for guide in guides {
+            match *guide {
+                Guide::Horizontal { y } => snap.rows.push(y),
+                Guide::Vertical { x } => snap.columns.push(x),
+            }
+        }
+        for area in others {
+            snap.columns.extend([area.left(), area.right() - 1]);
+            snap.rows.extend([area.top(), area.bottom() - 1]);
+        }
+        snap
+    }
+
+    /// Snaps a single cell, like the corner of a shape being drawn.
+    pub fn point(&self, point: Point) -> Point {
+        point.offset(self.offset(Area::new(point.x, point.y, 1, 1)))
+    }
+
+    /// How far to move `area` so that its edges snap into place. On the grid,
+    /// it's the top left corner that snaps.
+    pub fn offset(&self, area: Area) -> Offset {
+        Offset {
+            x: self.adjust(&self.columns, [area.left(), area.right() - 1]),
+            y: self.adjust(&self.rows, [area.top(), area.bottom() - 1]),
+        }
+    }
+
+    fn adjust(&self, targets: &[i32], edges: [i32; 2]) -> i32 {
+        let grid = self.grid as i32;
+        edges
+            .iter()
+            .flat_map(|edge| targets.iter().map(move |target| target - edge))
+            .filter(|by| by.abs() <= SNAP_DISTANCE)
+            .min_by_key(|by| by.abs())
+            .or_else(|| {
+                (grid > 1).then(|| (edges[0] + grid / 2).div_euclid(grid) * grid - edges[0])
+            })
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap() {
+        let snap = Snap::new(4, &[Guide::Vertical { x: 20 }], [Area::new(0, 10, 6, 3)]);
+
+        // The right edge lands on the guide, the bottom edge on the other box
+        assert_eq!(snap.offset(Area::new(14, 7, 5, 3)), Offset { x: 2, y: 1 });
+        // Too far from anything, so the top left corner goes onto the grid
+        assert_eq!(snap.point(Point::new(-7, 5)), Point::new(-8, 4));
+        assert_eq!(Snap::default().point(Point::new(-7, 5)), Point::new(-7, 5));
+    }
+}
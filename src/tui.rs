@@ -6,6 +6,7 @@ use std::{
     time::Duration,
 };
 
+use base64::{prelude::BASE64_STANDARD, Engine};
 use color_eyre::Result;
 use crossterm::{
     cursor,
@@ -13,6 +14,7 @@ use crossterm::{
         DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
         Event as CrosstermEvent, EventStream, KeyEvent, KeyEventKind, MouseEvent,
     },
+    style::Print,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen},
 };
 use futures::{FutureExt, StreamExt};
@@ -209,6 +211,16 @@ impl Tui {
         Ok(())
     }
 
+    /// Puts `text` into the system clipboard using an OSC 52 escape sequence.
+    /// This works over SSH, as long as the terminal emulator supports it.
+    pub fn copy_to_clipboard(&mut self, text: &str) -> Result<()> {
+        crossterm::execute!(
+            stdout(),
+            Print(format!("\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text)))
+        )?;
+        Ok(())
+    }
+
     pub async fn next_event(&mut self) -> Option<Event> {
         self.event_rx.recv().await
     }